@@ -8,6 +8,38 @@ use winit::{
     keyboard::PhysicalKey,
 };
 
+/// Maps window pixels into the coordinate space the app draws in, e.g. a virtual resolution
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CursorMapping {
+    /// Window position of the mapped space's origin
+    pub origin: (f32, f32),
+    /// Mapped units per window pixel
+    pub scale: (f32, f32),
+}
+
+impl Default for CursorMapping {
+    fn default() -> Self {
+        Self {
+            origin: (0.0, 0.0),
+            scale: (1.0, 1.0),
+        }
+    }
+}
+
+impl CursorMapping {
+    /// Maps a window position
+    pub fn position(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (
+            (x - self.origin.0) * self.scale.0,
+            (y - self.origin.1) * self.scale.1,
+        )
+    }
+    /// Maps a window movement
+    pub fn delta(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x * self.scale.0, y * self.scale.1)
+    }
+}
+
 #[derive(Default)]
 pub struct Input {
     keyboard: HashMap<KeyCode, (ElementState, ElementState)>, // (current, previous) state
@@ -15,6 +47,7 @@ pub struct Input {
     mouse_position: (f32, f32),
     mouse_delta: (f32, f32),
     mouse_wheel_delta: f32,
+    cursor_mapping: CursorMapping,
 }
 
 impl Input {
@@ -46,6 +79,11 @@ impl Input {
         self.mouse_position = pos;
     }
 
+    /// Set how window coords map to [`Self::mouse_position`]
+    pub(crate) fn set_cursor_mapping(&mut self, mapping: CursorMapping) {
+        self.cursor_mapping = mapping;
+    }
+
     /// Update mouse wheel delta
     pub(crate) fn update_scroll(&mut self, delta: f32) {
         self.mouse_wheel_delta += delta;
@@ -142,14 +180,20 @@ impl Input {
             .is_some_and(|(curr, _)| *curr == ElementState::Released)
     }
 
-    /// Current mouse cursor position in window coords
+    /// Current mouse cursor position in the app's drawing coords, window coords unless
+    /// a virtual resolution is set
     pub fn mouse_position(&self) -> (f32, f32) {
+        self.cursor_mapping.position(self.mouse_position)
+    }
+
+    /// Current mouse cursor position in window coords
+    pub fn window_mouse_position(&self) -> (f32, f32) {
         self.mouse_position
     }
 
-    /// Delta mouse movement since last frame
+    /// Delta mouse movement since last frame, in the same coords as [`Self::mouse_position`]
     pub fn mouse_delta(&self) -> (f32, f32) {
-        self.mouse_delta
+        self.cursor_mapping.delta(self.mouse_delta)
     }
 
     /// Mouse wheel delta this frame (positive = scroll up, negative = scroll down)
@@ -302,4 +346,20 @@ mod tests {
         assert!(input.key_held(KeyCode::KeyX));
         assert!(!input.key_released(KeyCode::KeyX));
     }
+
+    #[test]
+    fn cursor_mapping_scales_position_and_delta() {
+        // a 2x upscaled canvas offset by a 100px bar on the left
+        let mut input = Input::default();
+        input.set_cursor_mapping(CursorMapping {
+            origin: (100.0, 0.0),
+            scale: (0.5, 0.5),
+        });
+        input.inject_cursor(300.0, 200.0);
+        input.inject_cursor(310.0, 180.0);
+
+        assert_eq!(input.mouse_position(), (105.0, 90.0));
+        assert_eq!(input.mouse_delta(), (5.0, -10.0));
+        assert_eq!(input.window_mouse_position(), (310.0, 180.0));
+    }
 }
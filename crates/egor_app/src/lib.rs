@@ -1,7 +1,10 @@
 pub mod input;
 pub mod time;
 
-use crate::{input::Input, time::FrameTimer};
+use crate::{
+    input::{CursorMapping, Input},
+    time::FrameTimer,
+};
 use std::sync::Arc;
 pub use winit::{
     dpi::PhysicalSize,
@@ -70,6 +73,10 @@ pub trait AppHandler<R> {
     fn frame(&mut self, _window: &Window, _resource: &mut R, _input: &Input, _timer: &FrameTimer) {}
     /// Called on window resize
     fn resize(&mut self, _w: u32, _h: u32, _resource: &mut R) {}
+    /// How window coords map to [`Input::mouse_position`], queried before every frame
+    fn cursor_mapping(&self) -> CursorMapping {
+        CursorMapping::default()
+    }
 }
 
 /// Generic application entry point
@@ -164,6 +171,7 @@ impl<R, H: AppHandler<R> + 'static> ApplicationHandler<(R, H)> for AppRunner<R,
                 };
 
                 self.timer.update();
+                self.input.set_cursor_mapping(handler.cursor_mapping());
                 handler.frame(window, resource, &self.input, &self.timer);
                 self.input.end_frame();
 
@@ -202,6 +210,7 @@ impl<R, H: AppHandler<R> + 'static> ApplicationHandler<(R, H)> for AppRunner<R,
         let Some(window) = &self.window else { return };
 
         handler.on_ready(window, &mut resource);
+        self.input.set_cursor_mapping(handler.cursor_mapping());
         handler.frame(window, &mut resource, &self.input, &self.timer);
 
         window.set_visible(true);
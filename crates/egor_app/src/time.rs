@@ -1,9 +1,19 @@
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
+/// `performance.now()` reading the timer started at, in milliseconds
 #[cfg(target_arch = "wasm32")]
-fn now() -> f32 {
-    (web_sys::window().unwrap().performance().unwrap().now() / 1000.0) as f32
+type Instant = f64;
+
+/// Milliseconds since page load, only differences are meaningful
+#[cfg(target_arch = "wasm32")]
+fn performance_now() -> f64 {
+    web_sys::window().unwrap().performance().unwrap().now()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now(start: Instant) -> f32 {
+    ((performance_now() - start) / 1000.0) as f32
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -12,7 +22,6 @@ fn now(start: Instant) -> f32 {
 }
 
 pub struct FrameTimer {
-    #[cfg(not(target_arch = "wasm32"))]
     start: Instant,
     last_time: f32,
     accumulator: f32,
@@ -30,6 +39,8 @@ impl Default for FrameTimer {
         Self {
             #[cfg(not(target_arch = "wasm32"))]
             start: Instant::now(),
+            #[cfg(target_arch = "wasm32")]
+            start: performance_now(),
             last_time: 0.0,
             accumulator: 0.0,
             frame_count: 0,
@@ -41,18 +52,14 @@ impl Default for FrameTimer {
 }
 
 impl FrameTimer {
+    /// Time in seconds since the app started, as of the current frame
+    pub fn elapsed(&self) -> f32 {
+        self.last_time
+    }
+
     /// Updates delta time & calculates FPS
     pub(crate) fn update(&mut self) {
-        let cur_time = {
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                now(self.start)
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                now()
-            }
-        };
+        let cur_time = now(self.start);
 
         self.delta = cur_time - self.last_time;
         self.last_time = cur_time;
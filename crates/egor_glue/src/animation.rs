@@ -0,0 +1,577 @@
+use glam::{Vec2, vec2};
+
+use crate::{math::Rect, primitives::source_uvs};
+
+/// A single sprite-sheet frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    /// Region of the sheet in pixels, usable with `RectangleBuilder::source_rect`
+    pub source: Rect,
+    /// Normalized UV corners (TL, TR, BR, BL), usable with `RectangleBuilder::uv`
+    pub uv: [[f32; 2]; 4],
+    /// How long the frame is shown, in seconds
+    pub duration: f32,
+}
+
+impl Frame {
+    /// Create a frame from a pixel `source` region of a sheet of `sheet_size` pixels
+    pub fn new(source: Rect, sheet_size: Vec2, duration: f32) -> Self {
+        Self {
+            source,
+            uv: source_uvs(&source, sheet_size),
+            duration,
+        }
+    }
+}
+
+/// How a [`Clip`] advances past its last frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Wraps back to the first frame
+    #[default]
+    Loop,
+    /// Bounces back & forth between the first & last frame
+    PingPong,
+    /// Stops on the last frame
+    Once,
+}
+
+/// A named sequence of frame indices into a [`SpriteSheet`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub frames: Vec<usize>,
+    pub mode: PlayMode,
+}
+
+/// Frames & named tags sliced from a single texture
+#[derive(Clone, Debug, Default)]
+pub struct SpriteSheet {
+    size: Vec2,
+    frames: Vec<Frame>,
+    tags: Vec<Tag>,
+}
+
+impl SpriteSheet {
+    /// Create an empty sheet for a texture of `size` pixels
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Slice a sheet of `size` pixels into a `columns` x `rows` grid
+    ///
+    /// Takes the first `count` cells row by row, each shown for `duration` seconds
+    pub fn grid(size: Vec2, columns: usize, rows: usize, count: usize, duration: f32) -> Self {
+        let mut sheet = Self::new(size);
+        let cell = size / vec2(columns.max(1) as f32, rows.max(1) as f32);
+        for i in 0..count.min(columns * rows) {
+            let at = vec2((i % columns) as f32, (i / columns) as f32) * cell;
+            sheet.push_frame(Rect::new(at, cell), duration);
+        }
+        sheet
+    }
+
+    /// Add a frame from a pixel region & returns its index
+    pub fn push_frame(&mut self, source: Rect, duration: f32) -> usize {
+        self.frames.push(Frame::new(source, self.size, duration));
+        self.frames.len() - 1
+    }
+    /// Add a named tag over arbitrary frame indices
+    pub fn add_tag(
+        &mut self,
+        name: impl Into<String>,
+        frames: impl IntoIterator<Item = usize>,
+        mode: PlayMode,
+    ) {
+        self.tags.push(Tag {
+            name: name.into(),
+            frames: frames.into_iter().collect(),
+            mode,
+        });
+    }
+
+    /// Texture size in pixels
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+    /// All frames in sheet order
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+    /// All tags in the order they were added
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// Clip for a named tag, if it exists
+    /// Out of range frame indices are skipped
+    pub fn clip(&self, tag: &str) -> Option<Clip> {
+        let tag = self.tags.iter().find(|t| t.name == tag)?;
+        let frames = tag
+            .frames
+            .iter()
+            .filter_map(|&i| self.frames.get(i).copied())
+            .collect();
+        Some(Clip::new(frames, tag.mode))
+    }
+    /// Clip over every frame of the sheet
+    pub fn clip_all(&self, mode: PlayMode) -> Clip {
+        Clip::new(self.frames.clone(), mode)
+    }
+}
+
+/// An ordered list of frames & how to play them
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Clip {
+    pub frames: Vec<Frame>,
+    pub mode: PlayMode,
+}
+
+impl Clip {
+    pub fn new(frames: Vec<Frame>, mode: PlayMode) -> Self {
+        Self { frames, mode }
+    }
+    /// Returns the clip with a different [`PlayMode`]
+    pub fn with_mode(mut self, mode: PlayMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+/// Plays a [`Clip`], advanced with [`Self::update`]
+#[derive(Clone, Debug)]
+pub struct Animation {
+    clip: Clip,
+    current: usize,
+    timer: f32,
+    forward: bool,
+    finished: bool,
+    speed: f32,
+}
+
+impl Animation {
+    pub fn new(clip: Clip) -> Self {
+        Self {
+            clip,
+            current: 0,
+            timer: 0.0,
+            forward: true,
+            finished: false,
+            speed: 1.0,
+        }
+    }
+
+    /// Switches to another clip & restarts, unless it's already playing
+    pub fn play(&mut self, clip: &Clip) {
+        if self.clip != *clip {
+            self.clip = clip.clone();
+            self.reset();
+        }
+    }
+    /// Restarts from the first frame
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.timer = 0.0;
+        self.forward = true;
+        self.finished = false;
+    }
+    /// Sets the playback speed multiplier, 1.0 by default
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Advances playback by `dt` seconds
+    pub fn update(&mut self, dt: f32) {
+        if self.clip.frames.is_empty() {
+            return;
+        }
+        self.timer += dt * self.speed;
+
+        // Bounded so zero-length frames can't spin forever
+        for _ in 0..self.clip.frames.len() * 2 {
+            let duration = self.clip.frames[self.current].duration;
+            if self.finished || self.timer < duration {
+                break;
+            }
+            self.timer -= duration.max(0.0);
+            self.advance();
+        }
+    }
+
+    fn advance(&mut self) {
+        let last = self.clip.frames.len() - 1;
+        match self.clip.mode {
+            PlayMode::Loop => {
+                self.current = if self.current == last {
+                    0
+                } else {
+                    self.current + 1
+                }
+            }
+            PlayMode::Once if self.current == last => self.finished = true,
+            PlayMode::Once => self.current += 1,
+            PlayMode::PingPong if last == 0 => {}
+            PlayMode::PingPong => {
+                if (self.forward && self.current == last) || (!self.forward && self.current == 0) {
+                    self.forward = !self.forward;
+                }
+                self.current = if self.forward {
+                    self.current + 1
+                } else {
+                    self.current - 1
+                };
+            }
+        }
+    }
+
+    /// The clip being played
+    pub fn clip(&self) -> &Clip {
+        &self.clip
+    }
+    /// Index of the current frame within the clip
+    pub fn frame_index(&self) -> usize {
+        self.current
+    }
+    /// The current frame, `None` for an empty clip
+    pub fn frame(&self) -> Option<&Frame> {
+        self.clip.frames.get(self.current)
+    }
+    /// UV corners of the current frame, the full texture for an empty clip
+    pub fn uv(&self) -> [[f32; 2]; 4] {
+        self.frame()
+            .map(|f| f.uv)
+            .unwrap_or([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]])
+    }
+    /// Whether a [`PlayMode::Once`] clip has passed its last frame
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(feature = "json")]
+pub use json::AnimationError;
+
+#[cfg(feature = "json")]
+mod json {
+    use std::{collections::BTreeMap, fmt};
+
+    use glam::vec2;
+    use serde::{
+        Deserialize, Deserializer,
+        de::{MapAccess, SeqAccess, Visitor},
+    };
+
+    use super::{PlayMode, SpriteSheet};
+    use crate::math::Rect;
+
+    /// Error returned when a sprite-sheet export can't be imported
+    #[derive(Debug)]
+    pub enum AnimationError {
+        Json(serde_json::Error),
+        /// Frames packed rotated are not supported, disable rotation in the exporter
+        RotatedFrame(String),
+        /// A tag references a frame that doesn't exist
+        UnknownFrame {
+            tag: String,
+            frame: String,
+        },
+    }
+
+    impl fmt::Display for AnimationError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Json(e) => write!(f, "invalid sprite sheet JSON: {e}"),
+                Self::RotatedFrame(name) => write!(f, "rotated frame '{name}' is not supported"),
+                Self::UnknownFrame { tag, frame } => {
+                    write!(f, "tag '{tag}' references unknown frame '{frame}'")
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for AnimationError {}
+
+    impl From<serde_json::Error> for AnimationError {
+        fn from(e: serde_json::Error) -> Self {
+            Self::Json(e)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct RawRect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    }
+
+    #[derive(Deserialize)]
+    struct RawSize {
+        w: f32,
+        h: f32,
+    }
+
+    #[derive(Deserialize)]
+    struct RawFrame {
+        #[serde(default)]
+        filename: String,
+        frame: RawRect,
+        #[serde(default)]
+        rotated: bool,
+        /// Milliseconds, Aseprite only
+        duration: Option<f32>,
+    }
+
+    /// Frames exported either as an array or as a map keyed by file name (order preserved)
+    struct RawFrames(Vec<RawFrame>);
+
+    impl<'de> Deserialize<'de> for RawFrames {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct FramesVisitor;
+
+            impl<'de> Visitor<'de> for FramesVisitor {
+                type Value = RawFrames;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("an array or map of frames")
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RawFrames, A::Error> {
+                    let mut frames = Vec::new();
+                    while let Some(frame) = seq.next_element()? {
+                        frames.push(frame);
+                    }
+                    Ok(RawFrames(frames))
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawFrames, A::Error> {
+                    let mut frames = Vec::new();
+                    while let Some((name, mut frame)) = map.next_entry::<String, RawFrame>()? {
+                        frame.filename = name;
+                        frames.push(frame);
+                    }
+                    Ok(RawFrames(frames))
+                }
+            }
+
+            deserializer.deserialize_any(FramesVisitor)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RawTag {
+        name: String,
+        from: usize,
+        to: usize,
+        #[serde(default)]
+        direction: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RawMeta {
+        size: RawSize,
+        #[serde(default)]
+        frame_tags: Vec<RawTag>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawSheet {
+        frames: RawFrames,
+        meta: RawMeta,
+        /// Frame names per animation, TexturePacker (Pixi/Phaser) only
+        #[serde(default)]
+        animations: BTreeMap<String, Vec<String>>,
+    }
+
+    impl RawSheet {
+        fn parse(json: &str) -> Result<Self, AnimationError> {
+            let raw: Self = serde_json::from_str(json)?;
+            if let Some(frame) = raw.frames.0.iter().find(|f| f.rotated) {
+                return Err(AnimationError::RotatedFrame(frame.filename.clone()));
+            }
+            Ok(raw)
+        }
+
+        fn sheet(&self, default_duration: f32) -> SpriteSheet {
+            let mut sheet = SpriteSheet::new(vec2(self.meta.size.w, self.meta.size.h));
+            for f in &self.frames.0 {
+                let source = Rect::new(vec2(f.frame.x, f.frame.y), vec2(f.frame.w, f.frame.h));
+                let duration = f.duration.map_or(default_duration, |ms| ms / 1000.0);
+                sheet.push_frame(source, duration);
+            }
+            sheet
+        }
+    }
+
+    impl SpriteSheet {
+        /// Import an Aseprite JSON export (array or hash), durations & tags included
+        ///
+        /// Tag directions map to [`PlayMode::Loop`] or [`PlayMode::PingPong`],
+        /// reverse directions reverse the tag's frames
+        pub fn from_aseprite_json(json: &str) -> Result<Self, AnimationError> {
+            let raw = RawSheet::parse(json)?;
+            let mut sheet = raw.sheet(0.1);
+            let count = sheet.frames.len();
+
+            for tag in &raw.meta.frame_tags {
+                if tag.from.max(tag.to) >= count {
+                    return Err(AnimationError::UnknownFrame {
+                        tag: tag.name.clone(),
+                        frame: tag.from.max(tag.to).to_string(),
+                    });
+                }
+                let mut frames: Vec<usize> = (tag.from..=tag.to).collect();
+                if tag.direction.ends_with("reverse") {
+                    frames.reverse();
+                }
+                let mode = if tag.direction.starts_with("pingpong") {
+                    PlayMode::PingPong
+                } else {
+                    PlayMode::Loop
+                };
+                sheet.add_tag(&tag.name, frames, mode);
+            }
+            Ok(sheet)
+        }
+
+        /// Import a TexturePacker JSON export (array or hash), every frame shown for `duration` seconds
+        ///
+        /// Frame name lists under `animations` become looping tags
+        pub fn from_texture_packer_json(json: &str, duration: f32) -> Result<Self, AnimationError> {
+            let raw = RawSheet::parse(json)?;
+            let mut sheet = raw.sheet(duration);
+
+            for (name, frame_names) in &raw.animations {
+                let frames = frame_names
+                    .iter()
+                    .map(|frame| {
+                        raw.frames
+                            .0
+                            .iter()
+                            .position(|f| &f.filename == frame)
+                            .ok_or_else(|| AnimationError::UnknownFrame {
+                                tag: name.clone(),
+                                frame: frame.clone(),
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                sheet.add_tag(name, frames, PlayMode::Loop);
+            }
+            Ok(sheet)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(count: usize, mode: PlayMode) -> Clip {
+        SpriteSheet::grid(vec2(40.0, 10.0), 4, 1, count, 1.0).clip_all(mode)
+    }
+
+    fn play(anim: &mut Animation, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                anim.update(1.0);
+                anim.frame_index()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grid_slices_row_by_row() {
+        // 3x2 grid of a 96x64 sheet, 5 frames
+        let sheet = SpriteSheet::grid(vec2(96.0, 64.0), 3, 2, 5, 0.1);
+        assert_eq!(sheet.frames().len(), 5);
+
+        let frame = sheet.frames()[4];
+        assert_eq!(frame.source, Rect::new(vec2(32.0, 32.0), vec2(32.0, 32.0)));
+        assert_eq!(frame.uv[0], [1.0 / 3.0, 0.5]);
+    }
+
+    #[test]
+    fn play_modes() {
+        let mut looping = Animation::new(clip(3, PlayMode::Loop));
+        assert_eq!(play(&mut looping, 4), [1, 2, 0, 1]);
+
+        let mut ping_pong = Animation::new(clip(3, PlayMode::PingPong));
+        assert_eq!(play(&mut ping_pong, 5), [1, 2, 1, 0, 1]);
+
+        let mut once = Animation::new(clip(3, PlayMode::Once));
+        assert_eq!(play(&mut once, 3), [1, 2, 2]);
+        assert!(once.is_finished());
+    }
+
+    #[test]
+    fn per_frame_durations() {
+        let mut sheet = SpriteSheet::new(vec2(20.0, 10.0));
+        sheet.push_frame(Rect::new(vec2(0.0, 0.0), vec2(10.0, 10.0)), 0.5);
+        sheet.push_frame(Rect::new(vec2(10.0, 0.0), vec2(10.0, 10.0)), 0.25);
+        let mut anim = Animation::new(sheet.clip_all(PlayMode::Loop));
+
+        anim.update(0.6);
+        assert_eq!(anim.frame_index(), 1);
+        anim.update(0.2);
+        assert_eq!(anim.frame_index(), 0);
+    }
+
+    #[test]
+    fn tags_pick_frames() {
+        let mut sheet = SpriteSheet::grid(vec2(40.0, 10.0), 4, 1, 4, 0.1);
+        sheet.add_tag("tail", [3, 2], PlayMode::Once);
+
+        let clip = sheet.clip("tail").unwrap();
+        assert_eq!(clip.mode, PlayMode::Once);
+        assert_eq!(clip.frames[0].source.position, vec2(30.0, 0.0));
+        assert!(sheet.clip("missing").is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn aseprite_import() {
+        let json = r#"{
+            "frames": {
+                "walk 10.png": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "duration": 100 },
+                "walk 2.png": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "duration": 250 }
+            },
+            "meta": {
+                "size": { "w": 32, "h": 16 },
+                "frameTags": [{ "name": "walk", "from": 0, "to": 1, "direction": "pingpong" }]
+            }
+        }"#;
+        let sheet = SpriteSheet::from_aseprite_json(json).unwrap();
+
+        // hash order is kept
+        assert_eq!(sheet.frames()[1].source.position, vec2(16.0, 0.0));
+        assert_eq!(sheet.frames()[1].duration, 0.25);
+        assert_eq!(sheet.clip("walk").unwrap().mode, PlayMode::PingPong);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn texture_packer_import() {
+        let json = r#"{
+            "frames": [
+                { "filename": "idle.png", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": false },
+                { "filename": "jump.png", "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "rotated": false }
+            ],
+            "animations": { "hop": ["jump.png", "idle.png"] },
+            "meta": { "size": { "w": 16, "h": 8 } }
+        }"#;
+        let sheet = SpriteSheet::from_texture_packer_json(json, 0.2).unwrap();
+        assert_eq!(sheet.tags()[0].frames, [1, 0]);
+        assert_eq!(sheet.frames()[0].duration, 0.2);
+
+        let rotated = json.replacen(r#""rotated": false"#, r#""rotated": true"#, 1);
+        assert!(matches!(
+            SpriteSheet::from_texture_packer_json(&rotated, 0.2),
+            Err(AnimationError::RotatedFrame(_))
+        ));
+    }
+}
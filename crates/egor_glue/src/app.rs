@@ -1,15 +1,29 @@
 use std::sync::Arc;
 
-use crate::{graphics::Graphics, text::TextRenderer};
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+use crate::assets::AssetWatcher;
+use crate::{
+    color::Color,
+    graphics::{Graphics, SCREEN_CAMERA},
+    hit::HitRegistry,
+    math::Rect,
+    scaling::{Letterbox, ScaleMode},
+    text::TextRenderer,
+};
 
 #[cfg(feature = "ui")]
 use crate::ui::EguiRenderer;
 
 use egor_app::{
     AppConfig, AppHandler, AppRunner, ControlFlow, Fullscreen, PhysicalSize, Window, WindowEvent,
-    input::Input, time::FrameTimer,
+    input::{CursorMapping, Input},
+    time::FrameTimer,
+};
+use egor_render::{
+    Backbuffer, Device, DrawParams, FilterMode, GeometryBatch, RenderTarget, Renderer, Viewport,
+    target::OffscreenTarget, vertex::Vertex,
 };
-use egor_render::{Backbuffer, Device, RenderTarget, Renderer};
+use glam::{Vec2, vec2};
 
 type UpdateFn = dyn FnMut(&mut FrameContext);
 
@@ -64,6 +78,15 @@ pub struct App {
     #[cfg(feature = "ui")]
     egui: Option<EguiRenderer>,
     backbuffer: Option<Backbuffer>,
+    hits: HitRegistry,
+    virtual_resolution: Option<((u32, u32), ScaleMode)>,
+    letterbox_color: Color,
+    letterbox: Option<Letterbox>,
+    /// Target drawn into when a virtual resolution is set
+    canvas: Option<OffscreenTarget>,
+    blit: GeometryBatch,
+    #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+    assets: AssetWatcher,
 }
 
 impl Default for App {
@@ -84,6 +107,14 @@ impl App {
             #[cfg(feature = "ui")]
             egui: None,
             backbuffer: None,
+            hits: HitRegistry::default(),
+            virtual_resolution: None,
+            letterbox_color: Color::BLACK,
+            letterbox: None,
+            canvas: None,
+            blit: GeometryBatch::default(),
+            #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+            assets: AssetWatcher::default(),
         }
     }
 
@@ -142,6 +173,21 @@ impl App {
         self
     }
 
+    /// Render at a fixed `width` x `height` & scale it to the window with `mode`
+    ///
+    /// [`Graphics::screen_size`] & [`Input::mouse_position`] are in virtual pixels,
+    /// so the app looks the same on every display. egui still uses window pixels
+    pub fn virtual_resolution(mut self, width: u32, height: u32, mode: ScaleMode) -> Self {
+        self.virtual_resolution = Some(((width, height), mode));
+        self
+    }
+
+    /// Set the color of the bars around a letterboxed virtual resolution (defaults to black)
+    pub fn letterbox_color(mut self, color: Color) -> Self {
+        self.letterbox_color = color;
+        self
+    }
+
     /// Set the event loop control flow (defaults to [`ControlFlow::Poll`])
     ///
     /// - `ControlFlow::Poll`: continuously redraws (game-style loop)
@@ -241,11 +287,20 @@ impl AppHandler<Renderer> for App {
             return;
         };
 
-        let (w, h) = backbuffer.size();
+        let (w, h) = self.letterbox.map_or(backbuffer.size(), |l| l.canvas);
         let (device, queue) = (renderer.device().clone(), renderer.queue().clone());
         let format = backbuffer.format();
         let text_renderer = self.text_renderer.as_mut().unwrap();
 
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        self.assets.reload_changed(renderer);
+
+        let gfx = Graphics::new(renderer, text_renderer, format, w, h)
+            .with_globals(timer, input.mouse_position().into())
+            .with_hits(std::mem::take(&mut self.hits));
+        #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+        let gfx = gfx.with_assets(&mut self.assets);
+
         #[cfg(feature = "ui")]
         let egui_ctx = self.egui.as_mut().unwrap().begin_frame(_window);
         let mut ctx = FrameContext {
@@ -255,30 +310,68 @@ impl AppHandler<Renderer> for App {
                 backbuffer,
                 device: &device,
             },
-            gfx: Graphics::new(renderer, text_renderer, format, w, h),
+            gfx,
             input,
             timer,
             #[cfg(feature = "ui")]
             egui_ctx,
         };
         update(&mut ctx);
+        #[cfg(debug_assertions)]
+        ctx.gfx.draw_shader_errors();
 
         let mut geometry = ctx.gfx.flush();
+        self.hits = ctx.gfx.take_hits();
 
         text_renderer.prepare(&device, &queue, w, h);
 
         {
-            let mut r_pass = renderer.begin_render_pass(&mut frame.encoder, &frame.view);
-
-            for (tex_id, shader_id, batch) in &mut geometry {
-                renderer.draw_batch(&mut r_pass, batch, *tex_id, *shader_id);
+            let view = self
+                .canvas
+                .as_ref()
+                .map_or(&frame.view, |c| c.render_view());
+            let mut r_pass = renderer.begin_render_pass(&mut frame.encoder, view);
+
+            for (params, batch) in &mut geometry {
+                renderer.draw(&mut r_pass, batch, params);
             }
 
+            renderer.set_viewport(&mut r_pass, Viewport::full(w, h));
             text_renderer.render(&mut r_pass);
         }
 
+        if let (Some(canvas), Some(letterbox)) = (&self.canvas, self.letterbox) {
+            canvas.copy_to_sample(&mut frame.encoder);
+
+            // Draws the canvas as a quad covering its own pixel space, the viewport scales it
+            let uvs = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+            let corners = Rect::new(Vec2::ZERO, vec2(w as f32, h as f32)).corners();
+            let vertices: Vec<_> = corners
+                .iter()
+                .zip(uvs)
+                .map(|(c, uv)| Vertex::new(c.to_array(), [1.0; 4], uv))
+                .collect();
+            self.blit.push(&vertices, &[0, 1, 2, 2, 3, 0]);
+
+            let clear_color = renderer.clear_color();
+            renderer.set_clear_color(self.letterbox_color.into());
+            {
+                let mut r_pass = renderer.begin_render_pass(&mut frame.encoder, &frame.view);
+                let params = DrawParams {
+                    texture_id: canvas.texture_id(),
+                    shader_id: None,
+                    camera: SCREEN_CAMERA,
+                    viewport: Some(letterbox.viewport()),
+                    ..Default::default()
+                };
+                renderer.draw(&mut r_pass, &mut self.blit, &params);
+            }
+            renderer.set_clear_color(clear_color);
+        }
+
         #[cfg(feature = "ui")]
         {
+            let (w, h) = backbuffer.size();
             let render_data = self.egui.as_mut().unwrap().end_frame(_window);
             self.egui.as_mut().unwrap().render(
                 &device,
@@ -295,14 +388,35 @@ impl AppHandler<Renderer> for App {
     }
 
     fn resize(&mut self, w: u32, h: u32, renderer: &mut Renderer) {
-        self.backbuffer
-            .as_mut()
-            .unwrap()
-            .resize(renderer.device(), w, h);
+        let backbuffer = self.backbuffer.as_mut().unwrap();
+        backbuffer.resize(renderer.device(), w, h);
+
+        self.letterbox = self
+            .virtual_resolution
+            .map(|(size, mode)| mode.letterbox(size, (w, h)));
+        let (cw, ch) = self.letterbox.map_or((w, h), |l| l.canvas);
+        if let Some(((_, mode), _)) = self.virtual_resolution.zip(self.letterbox) {
+            let format = backbuffer.format();
+            let canvas = self
+                .canvas
+                .get_or_insert_with(|| renderer.create_offscreen_target(cw, ch, format));
+            canvas.resize(renderer.device(), cw, ch);
+            canvas.set_filter(match mode {
+                ScaleMode::IntegerScale => FilterMode::Nearest,
+                _ => FilterMode::Linear,
+            });
+            renderer.add_offscreen_texture(canvas);
+        }
+
         self.text_renderer
             .as_mut()
             .unwrap()
-            .resize(w, h, renderer.queue());
+            .resize(cw, ch, renderer.queue());
+    }
+
+    fn cursor_mapping(&self) -> CursorMapping {
+        self.letterbox
+            .map_or_else(CursorMapping::default, |l| l.cursor_mapping())
     }
 
     fn suspended(&mut self) {
@@ -0,0 +1,220 @@
+use std::{
+    cell::RefCell,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use egor_render::{ImageError, Preprocessor, ShaderError, SourceMap};
+
+/// Error returned when a shader or texture file can't be loaded
+#[derive(Debug)]
+pub enum AssetError {
+    /// The file couldn't be read
+    Io(PathBuf, io::Error),
+    /// The texture isn't a supported image
+    Image(PathBuf, ImageError),
+    /// The shader doesn't preprocess or validate
+    Shader(PathBuf, ShaderError),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "can't read {}: {e}", path.display()),
+            Self::Image(path, e) => write!(f, "{}: {e}", path.display()),
+            Self::Shader(path, e) => write!(f, "{}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+/// Reads a file, erroring with its path
+pub(crate) fn read(path: &Path) -> Result<Vec<u8>, AssetError> {
+    fs::read(path).map_err(|e| AssetError::Io(path.to_path_buf(), e))
+}
+
+/// A preprocessed shader file
+pub(crate) struct ShaderFile {
+    pub wgsl: String,
+    /// Traces errors in `wgsl` back to the file & its includes
+    pub map: SourceMap,
+    /// Every file it was built from, starting with the one it was loaded from
+    #[cfg_attr(not(feature = "hot_reload"), allow(dead_code))]
+    pub files: Vec<PathBuf>,
+}
+
+/// Preprocesses a shader file with the prelude, resolving `#include`s next to it
+pub(crate) fn read_shader(path: &Path) -> Result<ShaderFile, AssetError> {
+    let source = fs::read_to_string(path).map_err(|e| AssetError::Io(path.to_path_buf(), e))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let files = RefCell::new(vec![path.to_path_buf()]);
+    let (wgsl, map) = Preprocessor::new()
+        .with_loader(|include| {
+            let file = dir.join(include);
+            let source = fs::read_to_string(&file).ok()?;
+            files.borrow_mut().push(file);
+            Some(source)
+        })
+        .process_mapped(&source)
+        .map_err(|e| AssetError::Shader(path.to_path_buf(), e))?;
+    Ok(ShaderFile {
+        wgsl,
+        map,
+        files: files.into_inner(),
+    })
+}
+
+#[cfg(feature = "hot_reload")]
+pub(crate) use watcher::AssetWatcher;
+
+#[cfg(feature = "hot_reload")]
+mod watcher {
+    use std::{
+        collections::HashSet,
+        path::{Path, PathBuf},
+        sync::mpsc::{self, Receiver},
+    };
+
+    use egor_render::Renderer;
+    use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+    use super::{AssetError, read, read_shader};
+
+    #[derive(Clone, Copy)]
+    enum Asset {
+        Shader(usize),
+        Texture(usize),
+    }
+
+    /// Rebuilds shaders & textures loaded from files when the files change
+    pub(crate) struct AssetWatcher {
+        /// `None` if the platform watcher couldn't start, files then load without reloading
+        watcher: Option<RecommendedWatcher>,
+        events: Receiver<notify::Result<Event>>,
+        /// Watched directories, files are replaced rather than written by many editors
+        dirs: HashSet<PathBuf>,
+        /// Each asset with the files it was built from, the first being the one it was loaded from
+        assets: Vec<(Asset, Vec<PathBuf>)>,
+    }
+
+    impl Default for AssetWatcher {
+        fn default() -> Self {
+            let (sender, events) = mpsc::channel();
+            let watcher = notify::recommended_watcher(sender)
+                .inspect_err(|e| eprintln!("asset hot reload disabled: {e}"))
+                .ok();
+            Self {
+                watcher,
+                events,
+                dirs: HashSet::new(),
+                assets: Vec::new(),
+            }
+        }
+    }
+
+    impl AssetWatcher {
+        pub(crate) fn watch_shader(&mut self, id: usize, files: Vec<PathBuf>) {
+            self.watch(Asset::Shader(id), files);
+        }
+
+        pub(crate) fn watch_texture(&mut self, id: usize, path: &Path) {
+            self.watch(Asset::Texture(id), vec![path.to_path_buf()]);
+        }
+
+        fn watch(&mut self, asset: Asset, files: Vec<PathBuf>) {
+            let files: Vec<_> = files.iter().filter_map(|f| f.canonicalize().ok()).collect();
+            self.watch_dirs(&files);
+            self.assets.push((asset, files));
+        }
+
+        fn watch_dirs(&mut self, files: &[PathBuf]) {
+            let Some(watcher) = &mut self.watcher else {
+                return;
+            };
+            for dir in files.iter().filter_map(|f| f.parent()) {
+                if self.dirs.contains(dir) {
+                    continue;
+                }
+                match watcher.watch(dir, RecursiveMode::NonRecursive) {
+                    Ok(()) => _ = self.dirs.insert(dir.to_path_buf()),
+                    Err(e) => eprintln!("can't watch {}: {e}", dir.display()),
+                }
+            }
+        }
+
+        /// Rebuilds every asset whose files changed since the last call, in place under
+        /// the same id
+        /// Failures are logged & leave the previous version in use
+        pub(crate) fn reload_changed(&mut self, renderer: &mut Renderer) {
+            let changed: HashSet<PathBuf> = self
+                .events
+                .try_iter()
+                .filter_map(Result::ok)
+                .filter(|e| matches!(e.kind, EventKind::Create(_) | EventKind::Modify(_)))
+                .flat_map(|e| e.paths)
+                .collect();
+            if changed.is_empty() {
+                return;
+            }
+
+            let mut rebuilt = Vec::new();
+            for (i, (asset, files)) in self.assets.iter().enumerate() {
+                if !files.iter().any(|f| changed.contains(f)) {
+                    continue;
+                }
+                let path = &files[0];
+                let result = match *asset {
+                    Asset::Shader(id) => read_shader(path).and_then(|shader| {
+                        renderer
+                            .reload_shader_mapped(id, &shader.wgsl, &shader.map)
+                            .map_err(|e| AssetError::Shader(path.clone(), e))?;
+                        // includes may have been added or removed
+                        rebuilt.push((i, shader.files));
+                        Ok(())
+                    }),
+                    Asset::Texture(id) => read(path).and_then(|bytes| {
+                        renderer
+                            .try_update_texture(id, &bytes)
+                            .map_err(|e| AssetError::Image(path.clone(), e))
+                    }),
+                };
+                if let Err(e) = result {
+                    eprintln!("hot reload failed, keeping the previous version: {e}");
+                }
+            }
+
+            for (i, files) in rebuilt {
+                let files: Vec<_> = files.iter().filter_map(|f| f.canonicalize().ok()).collect();
+                self.watch_dirs(&files);
+                self.assets[i].1 = files;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shader_files_track_includes() {
+        let dir = std::env::temp_dir().join(format!("egor_assets_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (main, noise) = (dir.join("main.wgsl"), dir.join("noise.wgsl"));
+        fs::write(&noise, "fn noise() -> f32 { return 0.0; }").unwrap();
+        fs::write(&main, "#include \"noise.wgsl\"\nfn main_fn() {}").unwrap();
+
+        let shader = read_shader(&main).unwrap();
+        assert!(shader.wgsl.contains("fn noise()") && shader.wgsl.contains("fn vs_main"));
+        assert_eq!(shader.files, [main.clone(), noise]);
+
+        fs::write(&main, "#include \"missing.wgsl\"").unwrap();
+        assert!(matches!(read_shader(&main), Err(AssetError::Shader(..))));
+        assert!(matches!(
+            read_shader(&dir.join("none.wgsl")),
+            Err(AssetError::Io(..))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
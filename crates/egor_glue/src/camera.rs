@@ -1,13 +1,17 @@
-use glam::{Mat4, Vec2};
+use glam::{Mat2, Mat4, Vec2, vec2};
 
 use crate::math::Rect;
 
 /// A basic camera for controlling view & projection
 ///
 /// Useful for culling & rendering transformations
+#[derive(Clone, Copy, Debug)]
 pub struct Camera {
     position: Vec2,
     zoom: f32,
+    min_zoom: f32,
+    max_zoom: f32,
+    rotation: f32,
 }
 
 impl Default for Camera {
@@ -15,6 +19,9 @@ impl Default for Camera {
         Self {
             position: Vec2::ZERO,
             zoom: 1.0,
+            min_zoom: 0.1,
+            max_zoom: 10.0,
+            rotation: 0.0,
         }
     }
 }
@@ -30,7 +37,21 @@ impl Camera {
         let top = self.position.y;
         let bottom = self.position.y + height;
 
-        Mat4::orthographic_lh(left, right, bottom, top, -1.0, 1.0)
+        let proj = Mat4::orthographic_lh(left, right, bottom, top, -1.0, 1.0);
+        if self.rotation == 0.0 {
+            return proj;
+        }
+
+        // Rotate the world the opposite way around the view center
+        let center = self.view_center(screen_size).extend(0.0);
+        proj * Mat4::from_translation(center)
+            * Mat4::from_rotation_z(-self.rotation)
+            * Mat4::from_translation(-center)
+    }
+
+    /// World-space point shown at the center of the screen
+    fn view_center(&self, screen_size: Vec2) -> Vec2 {
+        self.position + screen_size / (2.0 * self.zoom)
     }
 
     /// Set the camera's position (top-left corner of view)
@@ -43,28 +64,115 @@ impl Camera {
         self.position = position - screen_size / (2.0 * self.zoom);
     }
 
-    /// Set zoom level, clamped between 0.1 & 10.0 to avoid insanity
+    /// Set zoom level, clamped to the zoom limits (0.1 to 10.0 by default)
     pub fn set_zoom(&mut self, zoom: f32) {
-        self.zoom = zoom.clamp(0.1, 10.0);
+        self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+    }
+
+    /// Set the range [`Self::set_zoom`] clamps to & re-clamps the current zoom  
+    /// Limits are kept positive & swapped if given in the wrong order
+    pub fn set_zoom_limits(&mut self, min: f32, max: f32) {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        self.min_zoom = min.max(f32::MIN_POSITIVE);
+        self.max_zoom = max.max(self.min_zoom);
+        self.set_zoom(self.zoom);
+    }
+
+    /// Returns the minimum & maximum zoom
+    pub fn zoom_limits(&self) -> (f32, f32) {
+        (self.min_zoom, self.max_zoom)
+    }
+
+    /// Multiplies the zoom by `factor` while keeping the world point under `screen_point` fixed,
+    /// e.g. the cursor when zooming with the mouse wheel
+    pub fn zoom_at(&mut self, screen_point: Vec2, factor: f32, screen_size: Vec2) {
+        let anchor = self.screen_to_world(screen_point, screen_size);
+        self.set_zoom(self.zoom * factor);
+        self.position += anchor - self.screen_to_world(screen_point, screen_size);
+    }
+
+    /// Returns the current zoom level
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Set the view rotation in radians around the center of the screen, increasing clockwise  
+    /// The world appears rotated the opposite way
+    pub fn set_rotation(&mut self, angle: f32) {
+        self.rotation = angle;
     }
 
-    /// Returns the viewport rectangle in world coordinates, factoring in zoom  
+    /// Returns the current rotation in radians
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    /// Moves the camera so the viewport stays inside `bounds`  
+    /// The view is centered on `bounds` along axes where it is larger than them
+    pub fn clamp_to(&mut self, bounds: &Rect, screen_size: Vec2) {
+        let rot = Mat2::from_angle(self.rotation);
+        let half = view_half_extents(screen_size, self.zoom, rot);
+        let center = clamp_center(self.view_center(screen_size), half, bounds);
+        self.center(center, screen_size);
+    }
+
+    /// Returns the bounding rectangle of the viewport in world coordinates, factoring in zoom
+    /// & rotation  
     /// Useful for culling or visibility checks
     pub fn viewport(&self, screen_size: Vec2) -> Rect {
-        let size = screen_size / self.zoom;
-        Rect::new(self.position, size)
+        if self.rotation == 0.0 {
+            return Rect::new(self.position, screen_size / self.zoom);
+        }
+        Rect::from_points(self.viewport_corners(screen_size))
     }
-    /// Converts a point from world space to screen space (pixels)
-    pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
-        (world - self.position) * self.zoom
+
+    /// Returns the world-space corners of the screen: top-left, top-right, bottom-right, bottom-left  
+    /// Unlike [`Self::viewport`] this is the exact, possibly rotated, visible area
+    pub fn viewport_corners(&self, screen_size: Vec2) -> [Vec2; 4] {
+        Rect::new(Vec2::ZERO, screen_size)
+            .corners()
+            .map(|c| self.screen_to_world(c, screen_size))
+    }
+
+    /// Converts a point from world space to screen space (pixels)  
+    /// `screen_size` is needed since a rotated view turns around its center
+    pub fn world_to_screen(&self, world: Vec2, screen_size: Vec2) -> Vec2 {
+        let center = self.view_center(screen_size);
+        let view = Mat2::from_angle(-self.rotation) * (world - center);
+        view * self.zoom + screen_size / 2.0
     }
 
     /// Converts a point from screen space back to world space
-    pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
-        screen / self.zoom + self.position
+    pub fn screen_to_world(&self, screen: Vec2, screen_size: Vec2) -> Vec2 {
+        let view = (screen - screen_size / 2.0) / self.zoom;
+        Mat2::from_angle(self.rotation) * view + self.view_center(screen_size)
     }
 }
 
+/// Half size of the axis-aligned bounds of a possibly rotated view, in world units
+pub(crate) fn view_half_extents(screen_size: Vec2, zoom: f32, rot: Mat2) -> Vec2 {
+    let half = screen_size / (2.0 * zoom);
+    let abs = Mat2::from_cols(rot.x_axis.abs(), rot.y_axis.abs());
+    abs * half
+}
+
+/// Moves a view center so a view of `half` extents stays inside `bounds`,
+/// centering on axes where the view is larger than the bounds
+pub(crate) fn clamp_center(center: Vec2, half: Vec2, bounds: &Rect) -> Vec2 {
+    let clamp_axis = |c: f32, h: f32, min: f32, max: f32| {
+        if max - min <= 2.0 * h {
+            (min + max) / 2.0
+        } else {
+            c.clamp(min + h, max - h)
+        }
+    };
+    let (min, max) = (bounds.min(), bounds.max());
+    vec2(
+        clamp_axis(center.x, half.x, min.x, max.x),
+        clamp_axis(center.y, half.y, min.y, max.y),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,10 +210,91 @@ mod tests {
         cam.target(vec2(100.0, 50.0));
         cam.set_zoom(2.0);
 
+        let screen_size = vec2(800.0, 600.0);
         let world = vec2(110.0, 55.0);
-        let screen = cam.world_to_screen(world);
-        let world2 = cam.screen_to_world(screen);
+        let screen = cam.world_to_screen(world, screen_size);
+        let world2 = cam.screen_to_world(screen, screen_size);
 
         assert!((world - world2).length() < 0.001);
+
+        cam.set_rotation(0.7);
+        let screen = cam.world_to_screen(world, screen_size);
+        let world2 = cam.screen_to_world(screen, screen_size);
+        assert!((world - world2).length() < 0.001);
+    }
+
+    #[test]
+    fn clamp_to_bounds() {
+        // the viewport is pushed back inside, or centered when the bounds are too small
+        let screen_size = vec2(200.0, 100.0);
+        let bounds = Rect::new(Vec2::ZERO, vec2(1000.0, 50.0));
+        let mut cam = Camera::default();
+        cam.target(vec2(-40.0, 300.0));
+        cam.clamp_to(&bounds, screen_size);
+
+        let view = cam.viewport(screen_size);
+        assert!((view.position - vec2(0.0, -25.0)).length() < 0.001);
+
+        cam.set_rotation(std::f32::consts::FRAC_PI_2);
+        cam.target(vec2(900.0, 0.0));
+        cam.clamp_to(&bounds, screen_size);
+        let view = cam.viewport(screen_size);
+        assert!((view.max().x - 1000.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn zoom_limits() {
+        // custom limits replace the default clamp, reversed limits are swapped
+        let mut cam = Camera::default();
+        cam.set_zoom(50.0);
+        assert_eq!(cam.zoom(), 10.0);
+
+        cam.set_zoom_limits(1000.0, 0.001);
+        assert_eq!(cam.zoom_limits(), (0.001, 1000.0));
+        cam.set_zoom(50.0);
+        assert_eq!(cam.zoom(), 50.0);
+
+        cam.set_zoom_limits(1.0, 2.0);
+        assert_eq!(cam.zoom(), 2.0);
+    }
+
+    #[test]
+    fn zoom_at_keeps_point_fixed() {
+        // the world point under the cursor stays under the cursor, with & without rotation
+        let screen_size = vec2(800.0, 600.0);
+        let cursor = vec2(600.0, 150.0);
+        for rotation in [0.0, 1.2] {
+            let mut cam = Camera::default();
+            cam.target(vec2(-30.0, 40.0));
+            cam.set_rotation(rotation);
+            let before = cam.screen_to_world(cursor, screen_size);
+
+            cam.zoom_at(cursor, 3.0, screen_size);
+            assert_eq!(cam.zoom(), 3.0);
+            let after = cam.screen_to_world(cursor, screen_size);
+            assert!((before - after).length() < 0.001);
+        }
+    }
+
+    #[test]
+    fn rotation_keeps_center() {
+        // rotating a quarter turn keeps the view center fixed & swaps the viewport extents
+        let screen_size = vec2(200.0, 100.0);
+        let mut cam = Camera::default();
+        cam.center(vec2(50.0, 50.0), screen_size);
+        cam.set_rotation(std::f32::consts::FRAC_PI_2);
+
+        let center = cam.world_to_screen(vec2(50.0, 50.0), screen_size);
+        assert!((center - screen_size / 2.0).length() < 0.001);
+
+        let rect = cam.viewport(screen_size);
+        assert!((rect.center() - vec2(50.0, 50.0)).length() < 0.001);
+        assert!((rect.size - vec2(100.0, 200.0)).length() < 0.001);
+
+        // the matrix agrees with the point conversion (clip space y points up)
+        let clip = cam.view_proj(screen_size) * vec2(60.0, 50.0).extend(0.0).extend(1.0);
+        let screen = cam.world_to_screen(vec2(60.0, 50.0), screen_size);
+        let expected = vec2(screen.x / 100.0 - 1.0, 1.0 - screen.y / 50.0);
+        assert!((clip.truncate().truncate() - expected).length() < 0.001);
     }
 }
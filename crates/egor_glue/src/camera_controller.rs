@@ -0,0 +1,352 @@
+use egor_app::input::{Input, KeyCode, MouseButton};
+use glam::{Mat2, Vec2, vec2};
+
+use crate::{
+    camera::{Camera, clamp_center, view_half_extents},
+    math::Rect,
+};
+
+/// Keys & buttons used by a [`CameraController`], empty lists disable an action
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraBindings {
+    /// Mouse buttons that drag the view
+    pub drag: Vec<MouseButton>,
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub zoom_in: Vec<KeyCode>,
+    pub zoom_out: Vec<KeyCode>,
+    /// Zoom around the cursor with the mouse wheel
+    pub wheel_zoom: bool,
+}
+
+impl Default for CameraBindings {
+    fn default() -> Self {
+        Self {
+            drag: vec![MouseButton::Middle],
+            up: vec![KeyCode::ArrowUp],
+            down: vec![KeyCode::ArrowDown],
+            left: vec![KeyCode::ArrowLeft],
+            right: vec![KeyCode::ArrowRight],
+            zoom_in: vec![KeyCode::Equal, KeyCode::NumpadAdd],
+            zoom_out: vec![KeyCode::Minus, KeyCode::NumpadSubtract],
+            wheel_zoom: true,
+        }
+    }
+}
+
+/// Input for one frame, separated from [`Input`] so the controller logic is testable
+#[derive(Clone, Copy, Debug, Default)]
+struct Controls {
+    cursor: Vec2,
+    /// Cursor movement in pixels while a drag button is held
+    drag: Option<Vec2>,
+    scroll: f32,
+    /// Keyboard pan direction in screen axes
+    pan: Vec2,
+    /// Keyboard zoom direction, positive zooms in
+    zoom: f32,
+}
+
+/// Pan & zoom camera for canvas-like apps
+///
+/// Drag with the middle mouse button, zoom around the cursor with the wheel &
+/// nudge with the arrow & +/- keys. Call [`Self::update`] once per frame
+/// ```no_run
+/// # use egor_glue::{camera_controller::CameraController, app::App};
+/// let mut controller = CameraController::default();
+/// App::new().run(move |ctx| {
+///     let screen = ctx.gfx.screen_size();
+///     controller.update(ctx.gfx.camera(), ctx.input, screen, ctx.timer.delta);
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct CameraController {
+    pub bindings: CameraBindings,
+    /// Keyboard pan speed in screen pixels per second
+    pub pan_speed: f32,
+    /// Zoom factor per mouse wheel notch
+    pub wheel_step: f32,
+    /// Zoom factor per second while a zoom key is held
+    pub key_zoom_speed: f32,
+    /// How quickly the view catches up with input, per second. 0 disables smoothing
+    pub smoothing: f32,
+    /// Whether a released drag keeps gliding
+    pub inertia: bool,
+    /// How quickly drag momentum decays, per second
+    pub friction: f32,
+    /// Fraction of the view kept free around a [`Self::fit_rect`] target
+    pub fit_padding: f32,
+    bounds: Option<Rect>,
+    fit: Option<Rect>,
+    /// Center & zoom the view is moving towards
+    target: Option<(Vec2, f32)>,
+    /// Center & zoom applied last frame, to notice outside camera changes
+    applied: Option<(Vec2, f32)>,
+    /// Drag momentum in world units per second
+    velocity: Vec2,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            bindings: CameraBindings::default(),
+            pan_speed: 600.0,
+            wheel_step: 1.1,
+            key_zoom_speed: 2.0,
+            smoothing: 15.0,
+            inertia: true,
+            friction: 6.0,
+            fit_padding: 0.05,
+            bounds: None,
+            fit: None,
+            target: None,
+            applied: None,
+            velocity: Vec2::ZERO,
+        }
+    }
+}
+
+impl CameraController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the key & button bindings
+    pub fn with_bindings(mut self, bindings: CameraBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+    /// Keeps the view inside `bounds` in world units, `None` to pan freely
+    pub fn set_bounds(&mut self, bounds: Option<Rect>) {
+        self.bounds = bounds;
+    }
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+    /// Animates the view to show all of `rect`, e.g. fit-to-content
+    pub fn fit_rect(&mut self, rect: Rect) {
+        self.fit = Some(rect);
+        self.velocity = Vec2::ZERO;
+    }
+    /// Whether the view is still moving towards its target
+    pub fn is_animating(&self) -> bool {
+        match (self.target, self.applied) {
+            (Some((tc, tz)), Some((c, z))) => {
+                tc.distance(c) * z > 0.5 || (tz / z).ln().abs() > 1e-3 || self.fit.is_some()
+            }
+            _ => self.fit.is_some(),
+        }
+    }
+
+    /// Reads `input` & moves `camera`, call once per frame with the frame's delta time
+    pub fn update(&mut self, camera: &mut Camera, input: &Input, screen_size: Vec2, dt: f32) {
+        let b = &self.bindings;
+        let held = |keys: &[KeyCode]| input.keys_held(keys) as i8 as f32;
+        let dragging = b.drag.iter().any(|&button| input.mouse_held(button));
+
+        let controls = Controls {
+            cursor: input.mouse_position().into(),
+            drag: dragging.then(|| input.mouse_delta().into()),
+            scroll: if b.wheel_zoom {
+                input.mouse_scroll()
+            } else {
+                0.0
+            },
+            pan: vec2(held(&b.right) - held(&b.left), held(&b.down) - held(&b.up)),
+            zoom: held(&b.zoom_in) - held(&b.zoom_out),
+        };
+        self.apply(camera, &controls, screen_size, dt);
+    }
+
+    fn apply(&mut self, camera: &mut Camera, controls: &Controls, screen_size: Vec2, dt: f32) {
+        let (min_zoom, max_zoom) = camera.zoom_limits();
+        let rot = Mat2::from_angle(camera.rotation());
+        let half_screen = screen_size / 2.0;
+
+        let current = (
+            camera.screen_to_world(half_screen, screen_size),
+            camera.zoom(),
+        );
+        // Start over from the camera if it was moved by someone else
+        let moved_outside = self.applied.is_none_or(|(c, z)| {
+            c.distance(current.0) * current.1 > 0.01 || (z - current.1).abs() > 1e-4
+        });
+        if moved_outside {
+            self.target = Some(current);
+            self.velocity = Vec2::ZERO;
+        }
+        let (mut center, mut zoom) = self.target.unwrap_or(current);
+        let (mut view_center, view_zoom) = current;
+
+        if let Some(rect) = self.fit.take() {
+            // Extents of the rect in (possibly rotated) view axes
+            let extents = rect
+                .corners()
+                .map(|c| rot.transpose() * (c - rect.center()))
+                .into_iter()
+                .fold(Vec2::ZERO, |e, c| e.max(c.abs()));
+            let fit =
+                screen_size * (1.0 - self.fit_padding) / (2.0 * extents.max(Vec2::splat(1e-6)));
+            center = rect.center();
+            zoom = fit.min_element().clamp(min_zoom, max_zoom);
+        }
+
+        if let Some(delta) = controls.drag {
+            // Dragging moves the view directly, smoothing would make it lag the cursor
+            let world_delta = rot * delta / view_zoom;
+            center -= world_delta;
+            view_center -= world_delta;
+            if dt > 0.0 {
+                self.velocity = -world_delta / dt;
+            }
+        } else if self.inertia {
+            center += self.velocity * dt;
+            self.velocity *= (-self.friction * dt).exp();
+            if self.velocity.length() * zoom < 1.0 {
+                self.velocity = Vec2::ZERO;
+            }
+        } else {
+            self.velocity = Vec2::ZERO;
+        }
+
+        center += rot * controls.pan * self.pan_speed * dt / zoom;
+
+        let mut zoom_around = |factor: f32, anchor: Vec2| {
+            let offset = rot * (anchor - half_screen);
+            let world = center + offset / zoom;
+            zoom = (zoom * factor).clamp(min_zoom, max_zoom);
+            center = world - offset / zoom;
+        };
+        if controls.scroll != 0.0 {
+            zoom_around(self.wheel_step.powf(controls.scroll), controls.cursor);
+        }
+        if controls.zoom != 0.0 {
+            zoom_around(self.key_zoom_speed.powf(controls.zoom * dt), half_screen);
+        }
+
+        if let Some(bounds) = self.bounds {
+            center = clamp_center(center, view_half_extents(screen_size, zoom, rot), &bounds);
+        }
+        self.target = Some((center, zoom));
+
+        // Ease towards the target, zoom in log space so zooming feels uniform
+        let t = if self.smoothing > 0.0 {
+            1.0 - (-self.smoothing * dt).exp()
+        } else {
+            1.0
+        };
+        let new_zoom = (view_zoom.ln() + (zoom.ln() - view_zoom.ln()) * t).exp();
+        let new_center = view_center + (center - view_center) * t;
+
+        camera.set_zoom(new_zoom);
+        camera.center(new_center, screen_size);
+        self.applied = Some((
+            camera.screen_to_world(half_screen, screen_size),
+            camera.zoom(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Vec2 = vec2(800.0, 600.0);
+
+    fn instant() -> CameraController {
+        CameraController {
+            smoothing: 0.0,
+            ..Default::default()
+        }
+    }
+
+    fn center(camera: &Camera) -> Vec2 {
+        camera.screen_to_world(SCREEN / 2.0, SCREEN)
+    }
+
+    #[test]
+    fn drag_pans_with_inertia() {
+        let mut camera = Camera::default();
+        camera.set_zoom(2.0);
+        let mut controller = instant();
+        let start = center(&camera);
+
+        let drag = Controls {
+            drag: Some(vec2(20.0, 0.0)),
+            ..Default::default()
+        };
+        controller.apply(&mut camera, &drag, SCREEN, 0.1);
+        // 20 pixels at 2x zoom is 10 world units, against the drag direction
+        assert!((center(&camera) - (start - vec2(10.0, 0.0))).length() < 1e-3);
+
+        let released = center(&camera);
+        controller.apply(&mut camera, &Controls::default(), SCREEN, 0.1);
+        assert!(center(&camera).x < released.x);
+    }
+
+    #[test]
+    fn wheel_zooms_around_cursor() {
+        let mut camera = Camera::default();
+        let mut controller = instant();
+        let cursor = vec2(700.0, 100.0);
+        let under_cursor = camera.screen_to_world(cursor, SCREEN);
+
+        let scroll = Controls {
+            cursor,
+            scroll: 3.0,
+            ..Default::default()
+        };
+        controller.apply(&mut camera, &scroll, SCREEN, 0.016);
+        assert!((camera.zoom() - 1.1f32.powi(3)).abs() < 1e-4);
+        assert!((camera.screen_to_world(cursor, SCREEN) - under_cursor).length() < 1e-3);
+    }
+
+    #[test]
+    fn fit_rect_eases_in() {
+        let mut camera = Camera::default();
+        let mut controller = CameraController {
+            fit_padding: 0.0,
+            ..Default::default()
+        };
+        let rect = Rect::new(vec2(1000.0, 1000.0), vec2(200.0, 100.0));
+        controller.fit_rect(rect);
+
+        controller.apply(&mut camera, &Controls::default(), SCREEN, 0.016);
+        assert!(controller.is_animating());
+        assert!(center(&camera).distance(rect.center()) > 1.0);
+
+        for _ in 0..200 {
+            controller.apply(&mut camera, &Controls::default(), SCREEN, 0.016);
+        }
+        assert!(!controller.is_animating());
+        assert!(center(&camera).distance(rect.center()) < 0.5);
+        assert!((camera.zoom() - 4.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bounds_clamp_view() {
+        let mut camera = Camera::default();
+        let mut controller = instant();
+        controller.set_bounds(Some(Rect::new(Vec2::ZERO, vec2(1000.0, 400.0))));
+
+        let pan = Controls {
+            pan: vec2(-1.0, 0.0),
+            ..Default::default()
+        };
+        controller.apply(&mut camera, &pan, SCREEN, 1.0);
+        // Wider than the view: clamped to the edge. Shorter than the view: centered
+        assert!((center(&camera) - vec2(400.0, 200.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn outside_changes_are_kept() {
+        let mut camera = Camera::default();
+        let mut controller = instant();
+        controller.apply(&mut camera, &Controls::default(), SCREEN, 0.016);
+
+        camera.center(vec2(-500.0, 20.0), SCREEN);
+        controller.apply(&mut camera, &Controls::default(), SCREEN, 0.016);
+        assert!((center(&camera) - vec2(-500.0, 20.0)).length() < 1e-3);
+    }
+}
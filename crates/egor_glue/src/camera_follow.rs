@@ -0,0 +1,190 @@
+use glam::{Mat2, Vec2};
+
+use crate::{camera::Camera, math::Rect};
+
+/// Smoothly keeps a moving target in view
+///
+/// Movement is exponential so it looks the same at any frame rate. Call
+/// [`Self::update`] once per frame after moving the target
+/// ```no_run
+/// # use egor_glue::{camera_follow::CameraFollow, app::App, math::vec2};
+/// let mut follow = CameraFollow::default().with_damping(8.0);
+/// let player = vec2(100.0, 100.0);
+/// App::new().run(move |ctx| {
+///     let screen = ctx.gfx.screen_size();
+///     follow.update(ctx.gfx.camera(), player, screen, ctx.timer.delta);
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct CameraFollow {
+    /// How quickly the camera catches up, per second. 0 snaps to the target
+    pub damping: f32,
+    /// World-space size of a box around the view center the target can move in
+    /// without moving the camera, aligned with the screen
+    pub dead_zone: Vec2,
+    /// Seconds of target movement to lead the view by
+    pub look_ahead: f32,
+    /// How quickly the lead adapts to changes in target velocity, per second
+    pub look_ahead_damping: f32,
+    /// World area the viewport is kept inside
+    pub bounds: Option<Rect>,
+    last_target: Option<Vec2>,
+    lead: Vec2,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            damping: 5.0,
+            dead_zone: Vec2::ZERO,
+            look_ahead: 0.0,
+            look_ahead_damping: 3.0,
+            bounds: None,
+            last_target: None,
+            lead: Vec2::ZERO,
+        }
+    }
+}
+
+impl CameraFollow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets how quickly the camera catches up, per second
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+    /// Sets the world-space box the target can move in without moving the camera
+    pub fn with_dead_zone(mut self, size: Vec2) -> Self {
+        self.dead_zone = size;
+        self
+    }
+    /// Sets how many seconds of target movement the view leads by
+    pub fn with_look_ahead(mut self, seconds: f32) -> Self {
+        self.look_ahead = seconds;
+        self
+    }
+    /// Keeps the viewport inside `bounds`
+    pub fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Moves `camera` towards `target`, `dt` is the frame's delta time in seconds
+    pub fn update(&mut self, camera: &mut Camera, target: Vec2, screen_size: Vec2, dt: f32) {
+        let velocity = match self.last_target {
+            Some(last) if dt > 0.0 => (target - last) / dt,
+            _ => Vec2::ZERO,
+        };
+        self.last_target = Some(target);
+        self.lead +=
+            (velocity * self.look_ahead - self.lead) * smoothing(self.look_ahead_damping, dt);
+
+        let center = camera.screen_to_world(screen_size / 2.0, screen_size);
+        let focus = target + self.lead;
+
+        // Only move by how far the focus left the dead zone, measured in screen axes
+        let rot = Mat2::from_angle(camera.rotation());
+        let offset = rot.transpose() * (focus - center);
+        let half = self.dead_zone / 2.0;
+        let outside = offset - offset.clamp(-half, half);
+        let desired = center + rot * outside;
+
+        camera.center(
+            center + (desired - center) * smoothing(self.damping, dt),
+            screen_size,
+        );
+        if let Some(bounds) = self.bounds {
+            camera.clamp_to(&bounds, screen_size);
+        }
+    }
+
+    /// Centers `camera` on `target` immediately, e.g. after a teleport or level load
+    pub fn snap(&mut self, camera: &mut Camera, target: Vec2, screen_size: Vec2) {
+        self.last_target = Some(target);
+        self.lead = Vec2::ZERO;
+        camera.center(target, screen_size);
+        if let Some(bounds) = self.bounds {
+            camera.clamp_to(&bounds, screen_size);
+        }
+    }
+}
+
+/// Fraction of the remaining distance covered in `dt` when closing it at `rate` per second
+fn smoothing(rate: f32, dt: f32) -> f32 {
+    if rate > 0.0 {
+        1.0 - (-rate * dt).exp()
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::vec2;
+
+    const SCREEN: Vec2 = vec2(800.0, 600.0);
+
+    fn center(camera: &Camera) -> Vec2 {
+        camera.screen_to_world(SCREEN / 2.0, SCREEN)
+    }
+
+    fn follow_for(fps: f32, seconds: f32, follow: &mut CameraFollow, target: Vec2) -> Vec2 {
+        let mut camera = Camera::default();
+        camera.center(Vec2::ZERO, SCREEN);
+        for _ in 0..(fps * seconds) as usize {
+            follow.update(&mut camera, target, SCREEN, 1.0 / fps);
+        }
+        center(&camera)
+    }
+
+    #[test]
+    fn frame_rate_independent() {
+        let target = vec2(300.0, -120.0);
+        let slow = follow_for(30.0, 0.5, &mut CameraFollow::default(), target);
+        let fast = follow_for(144.0, 0.5, &mut CameraFollow::default(), target);
+
+        assert!(slow.distance(target) > 1.0);
+        assert!(slow.distance(fast) < 0.01);
+    }
+
+    #[test]
+    fn dead_zone_holds_still() {
+        let mut follow = CameraFollow::new()
+            .with_damping(0.0)
+            .with_dead_zone(vec2(100.0, 100.0));
+
+        assert_eq!(
+            follow_for(60.0, 0.1, &mut follow, vec2(40.0, -40.0)),
+            Vec2::ZERO
+        );
+        // leaving the box drags the view along by the overshoot
+        let c = follow_for(60.0, 0.1, &mut follow, vec2(80.0, 0.0));
+        assert!(c.distance(vec2(30.0, 0.0)) < 0.001);
+    }
+
+    #[test]
+    fn look_ahead_leads_movement() {
+        let mut follow = CameraFollow::new().with_damping(0.0).with_look_ahead(0.5);
+        let mut camera = Camera::default();
+        follow.snap(&mut camera, Vec2::ZERO, SCREEN);
+
+        // move right at 100 units per second for a while
+        for i in 1..=240 {
+            let target = vec2(i as f32 * 100.0 / 60.0, 0.0);
+            follow.update(&mut camera, target, SCREEN, 1.0 / 60.0);
+        }
+        let ahead = center(&camera).x - 400.0;
+        assert!((ahead - 50.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn bounds_clamp() {
+        let bounds = Rect::new(Vec2::ZERO, vec2(2000.0, 2000.0));
+        let mut follow = CameraFollow::new().with_damping(0.0).with_bounds(bounds);
+        let c = follow_for(60.0, 0.1, &mut follow, vec2(-500.0, 1900.0));
+        assert!(c.distance(vec2(400.0, 1700.0)) < 0.001);
+    }
+}
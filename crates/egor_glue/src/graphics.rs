@@ -1,14 +1,95 @@
-use egor_render::{GeometryBatch, RenderTarget, Renderer, TextureFormat, target::OffscreenTarget};
-use glam::Vec2;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
+use bytemuck::Pod;
+use egor_app::time::FrameTimer;
+use egor_render::{
+    CameraUniform, DrawParams, GeometryBatch, MAX_SHADER_TEXTURES, RenderTarget, Renderer,
+    ShaderBindings, ShaderError, TextureFormat, Uniform, Viewport, target::OffscreenTarget,
+};
+use glam::{Vec2, vec2};
+
+#[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+use crate::assets::AssetWatcher;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::assets::{self, AssetError};
+use crate::hit::HitRegistry;
+use crate::mesh::{Mesh, MeshBuilder};
 use crate::primitives::ShapeBuilder;
+#[cfg(feature = "svg")]
+use crate::svg::SvgDocument;
 use crate::{
     camera::Camera,
     color::Color,
-    primitives::{PolygonBuilder, PolylineBuilder, PrimitiveBatch, RectangleBuilder},
+    math::Rect,
+    primitives::{
+        NineSliceBuilder, PolygonBuilder, PolylineBuilder, PrimitiveBatch, RectangleBuilder,
+    },
     text::{TextBuilder, TextRenderer},
 };
 
+/// Camera slot of geometry drawn in world space with [`Graphics::camera`]
+const WORLD_CAMERA: usize = 0;
+/// Camera slot of geometry drawn in [`Graphics::screen_space`]
+pub(crate) const SCREEN_CAMERA: usize = 1;
+
+/// Screen pixels the visible area is grown by before culling, covers antialiasing fringes
+const CULL_MARGIN_PX: f32 = 2.0;
+
+/// Counts of draws submitted to [`Graphics`] & rejected by view culling
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullStats {
+    /// Primitives & meshes submitted
+    pub submitted: usize,
+    /// Submissions skipped because they were outside the view
+    pub culled: usize,
+}
+
+impl CullStats {
+    /// Submissions that were tessellated & batched
+    pub fn drawn(&self) -> usize {
+        self.submitted - self.culled
+    }
+}
+
+/// Values shared by every camera slot's uniform
+#[derive(Clone, Copy, Debug, Default)]
+struct FrameGlobals {
+    time: f32,
+    delta: f32,
+    frame: u32,
+    /// Mouse position in target pixels
+    mouse: Vec2,
+}
+
+/// A camera & the pixel rectangle of the target it draws into
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CameraSlot {
+    pub(crate) camera: Camera,
+    pub(crate) viewport: Rect,
+}
+
+impl CameraSlot {
+    /// The world & screen slots covering a whole `w` x `h` target
+    fn defaults(w: u32, h: u32) -> Vec<Self> {
+        let slot = Self {
+            camera: Camera::default(),
+            viewport: Rect::new(Vec2::ZERO, vec2(w as f32, h as f32)),
+        };
+        vec![slot; 2]
+    }
+
+    fn gpu_viewport(&self) -> Viewport {
+        let Rect { position, size } = self.viewport;
+        Viewport {
+            x: position.x as u32,
+            y: position.y as u32,
+            width: size.x as u32,
+            height: size.y as u32,
+        }
+    }
+}
+
 /// High-level 2D drawing interface that simplifies the [`Renderer`]
 pub struct Graphics<'a> {
     renderer: &'a mut Renderer,
@@ -16,8 +97,23 @@ pub struct Graphics<'a> {
     camera: Camera,
     text_renderer: &'a mut TextRenderer,
     target_format: TextureFormat,
-    target_size: (u32, u32),
     current_shader: Option<usize>,
+    /// Parameter block of the innermost [`Self::with_shader_params`]
+    current_params: Option<u32>,
+    /// Extra shader textures of the innermost [`Self::with_textures`]
+    current_textures: [Option<usize>; MAX_SHADER_TEXTURES],
+    in_screen_space: bool,
+    culling: bool,
+    /// Cameras & viewports indexed by slot, the first two cover the whole target
+    slots: Vec<CameraSlot>,
+    /// Slots of the innermost [`Self::viewport`], world then screen
+    active_slots: (usize, usize),
+    globals: FrameGlobals,
+    /// Outlines recorded during the previous frame
+    last_hits: HitRegistry,
+    /// Rebuilds assets loaded from files when they change
+    #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+    assets: Option<&'a mut AssetWatcher>,
 }
 
 impl<'a> Graphics<'a> {
@@ -29,17 +125,51 @@ impl<'a> Graphics<'a> {
         w: u32,
         h: u32,
     ) -> Self {
+        renderer.clear_params();
         Self {
             renderer,
             batch: PrimitiveBatch::default(),
             camera: Camera::default(),
             text_renderer,
             target_format: format,
-            target_size: (w, h),
             current_shader: None,
+            current_params: None,
+            current_textures: [None; MAX_SHADER_TEXTURES],
+            in_screen_space: false,
+            culling: true,
+            slots: CameraSlot::defaults(w, h),
+            active_slots: (WORLD_CAMERA, SCREEN_CAMERA),
+            globals: FrameGlobals::default(),
+            last_hits: HitRegistry::default(),
+            #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+            assets: None,
         }
     }
 
+    /// Sets the frame timing & mouse position (in target pixels) shaders receive
+    pub(crate) fn with_globals(mut self, timer: &FrameTimer, mouse: Vec2) -> Self {
+        self.globals = FrameGlobals {
+            time: timer.elapsed(),
+            delta: timer.delta,
+            frame: timer.frame as u32,
+            mouse,
+        };
+        self
+    }
+
+    /// Sets the outlines recorded during the previous frame for hit testing
+    pub(crate) fn with_hits(mut self, hits: HitRegistry) -> Self {
+        self.last_hits = hits;
+        self
+    }
+
+    /// Sets the watcher files loaded this frame are registered with
+    #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+    pub(crate) fn with_assets(mut self, assets: &'a mut AssetWatcher) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+
     /// Create a new offscreen render target
     pub fn create_offscreen(&self, width: u32, height: u32) -> OffscreenTarget {
         self.renderer
@@ -47,6 +177,8 @@ impl<'a> Graphics<'a> {
     }
 
     /// Render to an offscreen target
+    ///
+    /// Shaders see this frame's globals, with the mouse scaled from the screen to the target
     pub fn render_offscreen(
         &mut self,
         target: &mut OffscreenTarget,
@@ -54,15 +186,31 @@ impl<'a> Graphics<'a> {
     ) {
         let (w, h) = target.size();
         let format = target.format();
+        // the target usually covers the screen, e.g. for post-processing
+        let target_scale =
+            vec2(w as f32, h as f32) / self.slots[SCREEN_CAMERA].viewport.size.max(Vec2::ONE);
+        let globals = FrameGlobals {
+            mouse: self.globals.mouse * target_scale,
+            ..self.globals
+        };
 
         let mut offscreen_gfx = Graphics {
             renderer: self.renderer,
             batch: PrimitiveBatch::default(),
             camera: Camera::default(),
             text_renderer: self.text_renderer,
-            target_size: (w, h),
             target_format: format,
             current_shader: None,
+            current_params: None,
+            current_textures: [None; MAX_SHADER_TEXTURES],
+            in_screen_space: false,
+            culling: true,
+            slots: CameraSlot::defaults(w, h),
+            active_slots: (WORLD_CAMERA, SCREEN_CAMERA),
+            globals,
+            last_hits: HitRegistry::default(),
+            #[cfg(all(feature = "hot_reload", not(target_arch = "wasm32")))]
+            assets: self.assets.as_deref_mut(),
         };
 
         render_fn(&mut offscreen_gfx);
@@ -79,9 +227,8 @@ impl<'a> Graphics<'a> {
                 .renderer
                 .begin_render_pass(&mut encoder, target.render_view());
 
-            for (tex_id, shader_id, batch) in &mut geometry {
-                self.renderer
-                    .draw_batch(&mut r_pass, batch, *tex_id, *shader_id);
+            for (params, batch) in &mut geometry {
+                self.renderer.draw(&mut r_pass, batch, params);
             }
         }
 
@@ -95,46 +242,201 @@ impl<'a> Graphics<'a> {
         self.renderer.add_offscreen_texture(target)
     }
 
-    /// Upload camera matrix & extract batched geometry
-    pub(crate) fn flush(&mut self) -> Vec<(Option<usize>, Option<usize>, GeometryBatch)> {
-        let (w, h) = self.target_size;
-        self.renderer.upload_camera_matrix(
-            self.camera
-                .view_proj((w as f32, h as f32).into())
-                .to_cols_array_2d(),
-        );
-        self.batch.take()
+    /// Upload camera matrices & extract batched geometry
+    pub(crate) fn flush(&mut self) -> Vec<(DrawParams, GeometryBatch)> {
+        self.slots[WORLD_CAMERA].camera = self.camera;
+        let FrameGlobals {
+            time,
+            delta,
+            frame,
+            mouse,
+        } = self.globals;
+        let uniforms: Vec<_> = self
+            .slots
+            .iter()
+            .map(|s| CameraUniform {
+                view_proj: s.camera.view_proj(s.viewport.size).to_cols_array_2d(),
+                time,
+                delta,
+                frame,
+                zoom: s.camera.zoom(),
+                resolution: s.viewport.size.to_array(),
+                mouse: (mouse - s.viewport.position).to_array(),
+            })
+            .collect();
+        self.renderer.upload_cameras(&uniforms);
+        self.renderer.upload_params();
+        self.batch.hits.finish(&self.slots);
+
+        let mut geometry = self.batch.take();
+        for (params, _) in &mut geometry {
+            params.viewport = self.slots.get(params.camera).map(CameraSlot::gpu_viewport);
+        }
+        geometry
+    }
+
+    /// Takes the outlines recorded this frame, call after [`Self::flush`]
+    pub(crate) fn take_hits(&mut self) -> HitRegistry {
+        std::mem::take(&mut self.batch.hits)
+    }
+
+    /// Returns the id of the topmost primitive under a screen-space `point`, e.g. the mouse position
+    ///
+    /// Only primitives drawn with `.id(..)` are hit. Queries the previous frame,
+    /// so it matches what is on screen when input arrives
+    pub fn hit_test(&self, point: Vec2) -> Option<u64> {
+        self.last_hits.at(point).first().copied()
+    }
+    /// Returns the ids of all primitives under a screen-space `point`, topmost first
+    pub fn hits_at(&self, point: Vec2) -> Vec<u64> {
+        self.last_hits.at(point)
+    }
+    /// Returns the ids of all primitives overlapping a screen-space `rect`, topmost first
+    pub fn hits_in_rect(&self, rect: &Rect) -> Vec<u64> {
+        self.last_hits.in_rect(rect)
     }
 
     /// Clear the screen to a color
     pub fn clear(&mut self, color: Color) {
         self.renderer.set_clear_color(color.into());
     }
-    /// Get current surface size in pixels
+    /// Get current surface size in pixels, or the size of the current [`Self::viewport`]
     pub fn screen_size(&self) -> Vec2 {
-        let (w, h) = self.target_size;
-        (w as f32, h as f32).into()
+        self.slots[self.active_slots.1].viewport.size
     }
     /// Mutable access to [`Camera`]
     pub fn camera(&mut self) -> &mut Camera {
         &mut self.camera
     }
 
+    /// Draw in screen space, in pixels from the top-left corner of the target
+    ///
+    /// Primitives drawn in the closure ignore [`Self::camera`], e.g. for a HUD drawn
+    /// on top of a moving world. Draw it after world content
+    pub fn screen_space(&mut self, mut render_fn: impl FnMut(&mut Self)) {
+        let previous = self.in_screen_space;
+        self.in_screen_space = true;
+        render_fn(self);
+        self.in_screen_space = previous;
+    }
+
+    /// Draw into a sub-rectangle of the target with its own camera, e.g. a minimap or split-screen pane
+    ///
+    /// `rect` is in pixels relative to the current viewport & clips everything drawn
+    /// in the closure. Inside, [`Self::camera`] is a copy of `camera` &
+    /// [`Self::screen_size`] is the size of `rect`. Text is not clipped
+    /// ```no_run
+    /// # use egor_glue::{app::App, camera::Camera, math::{Rect, vec2}};
+    /// let mut players = [Camera::default(), Camera::default()];
+    /// App::new().run(move |ctx| {
+    ///     let half = ctx.gfx.screen_size() * vec2(0.5, 1.0);
+    ///     for (i, camera) in players.iter().enumerate() {
+    ///         let pane = Rect::new(vec2(half.x * i as f32, 0.0), half);
+    ///         ctx.gfx.viewport(pane, camera, |gfx| {
+    ///             gfx.rect().at(vec2(100.0, 100.0));
+    ///         });
+    ///     }
+    /// });
+    /// ```
+    pub fn viewport(&mut self, rect: Rect, camera: &Camera, mut render_fn: impl FnMut(&mut Self)) {
+        let parent = self.slots[self.active_slots.1].viewport;
+        let min = (parent.position + rect.min()).round().max(parent.min());
+        let max = (parent.position + rect.max()).round().min(parent.max());
+        let viewport = Rect::new(min, (max - min).max(Vec2::ZERO));
+
+        let world = self.slots.len();
+        self.slots.push(CameraSlot {
+            camera: *camera,
+            viewport,
+        });
+        self.slots.push(CameraSlot {
+            camera: Camera::default(),
+            viewport,
+        });
+
+        let outer_camera = std::mem::replace(&mut self.camera, *camera);
+        let outer_slots = std::mem::replace(&mut self.active_slots, (world, world + 1));
+        let outer_screen_space = std::mem::replace(&mut self.in_screen_space, false);
+        render_fn(self);
+        self.slots[world].camera = std::mem::replace(&mut self.camera, outer_camera);
+        self.active_slots = outer_slots;
+        self.in_screen_space = outer_screen_space;
+    }
+
+    /// Enables or disables view culling, on by default
+    ///
+    /// Primitives whose bounds are outside the view of the camera at the time they are
+    /// drawn are skipped. Disable it when moving the camera after drawing
+    pub fn set_culling(&mut self, enabled: bool) {
+        self.culling = enabled;
+    }
+    /// Draws submitted & culled so far this frame
+    pub fn cull_stats(&self) -> CullStats {
+        self.batch.cull_stats
+    }
+
+    /// Syncs camera state builders depend on into the batch
+    fn sync_batch(&mut self) {
+        let (world, screen) = self.active_slots;
+        let screen_size = self.screen_size();
+        self.batch.params = self.current_params;
+        self.batch.extra_textures = self.current_textures;
+        let view = if self.in_screen_space {
+            self.batch.camera = screen;
+            self.batch.zoom = 1.0;
+            Rect::new(Vec2::ZERO, screen_size)
+        } else {
+            self.batch.camera = world;
+            self.batch.zoom = self.camera.zoom();
+            self.camera.viewport(screen_size)
+        };
+        let margin = Vec2::splat(CULL_MARGIN_PX / self.batch.zoom);
+        self.batch.visible = self
+            .culling
+            .then(|| Rect::new(view.position - margin, view.size + margin * 2.0));
+    }
+    /// Syncs the batch & returns it
+    fn batch(&mut self) -> &mut PrimitiveBatch {
+        self.sync_batch();
+        &mut self.batch
+    }
+
     /// Start building a rectangle primitive
     pub fn rect(&mut self) -> RectangleBuilder<'_> {
-        RectangleBuilder::new(&mut self.batch, self.current_shader)
+        let shader_id = self.current_shader;
+        self.sync_batch();
+        RectangleBuilder::new(&mut self.batch, self.renderer, shader_id)
+    }
+    /// Start building a nine-slice rectangle from a texture
+    pub fn nine_slice(&mut self, texture_id: usize) -> NineSliceBuilder<'_> {
+        let shader_id = self.current_shader;
+        self.sync_batch();
+        NineSliceBuilder::new(&mut self.batch, self.renderer, shader_id, texture_id)
     }
     /// Start building an arbitrary polygon primitive, capable of triangles, circles, n-gons
     pub fn polygon(&mut self) -> PolygonBuilder<'_> {
-        PolygonBuilder::new(&mut self.batch, self.current_shader)
+        let shader_id = self.current_shader;
+        PolygonBuilder::new(self.batch(), shader_id)
     }
     /// Start building a polyline (stroked path) primitive
     pub fn polyline(&mut self) -> PolylineBuilder<'_> {
-        PolylineBuilder::new(&mut self.batch, self.current_shader)
+        let shader_id = self.current_shader;
+        PolylineBuilder::new(self.batch(), shader_id)
     }
     /// Start building a shape
     pub fn shape(&mut self) -> ShapeBuilder<'_> {
-        ShapeBuilder::new(&mut self.batch, self.current_shader)
+        let shader_id = self.current_shader;
+        ShapeBuilder::new(self.batch(), shader_id)
+    }
+    /// Start drawing a pre-tessellated [`Mesh`]
+    pub fn mesh<'m>(&'m mut self, mesh: &'m Mesh) -> MeshBuilder<'m> {
+        let shader_id = self.current_shader;
+        MeshBuilder::new(self.batch(), shader_id, mesh)
+    }
+    /// Start drawing a parsed [`SvgDocument`], top-left at the origin by default
+    #[cfg(feature = "svg")]
+    pub fn svg<'m>(&'m mut self, doc: &'m SvgDocument) -> MeshBuilder<'m> {
+        self.mesh(doc.mesh())
     }
     /// Load a font from disk into the text system.
     pub fn load_font(&mut self, bytes: &[u8]) -> Option<String> {
@@ -152,6 +454,32 @@ impl<'a> Graphics<'a> {
     pub fn load_texture(&mut self, data: &[u8]) -> usize {
         self.renderer.add_texture(data)
     }
+    /// Get the dimensions of a loaded texture in pixels
+    pub fn texture_size(&self, id: usize) -> Option<Vec2> {
+        let (w, h) = self.renderer.texture_size(id)?;
+        Some(vec2(w as f32, h as f32))
+    }
+    /// Load a texture from raw RGBA8 pixels, e.g. generated noise or a palette
+    pub fn load_texture_raw(&mut self, w: u32, h: u32, data: &[u8]) -> usize {
+        self.renderer.add_texture_raw(w, h, data)
+    }
+    /// Load a texture from an image file
+    ///
+    /// With the `hot_reload` feature the file is watched & the texture is replaced under
+    /// the same id when it changes, a file that doesn't decode keeps the previous image
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_texture_file(&mut self, path: impl AsRef<Path>) -> Result<usize, AssetError> {
+        let path = path.as_ref();
+        let id = self
+            .renderer
+            .try_add_texture(&assets::read(path)?)
+            .map_err(|e| AssetError::Image(path.to_path_buf(), e))?;
+        #[cfg(feature = "hot_reload")]
+        if let Some(assets) = self.assets.as_deref_mut() {
+            assets.watch_texture(id, path);
+        }
+        Ok(id)
+    }
     /// Update texture data by index
     pub fn update_texture(&mut self, index: usize, data: &[u8]) {
         self.renderer.update_texture(index, data);
@@ -162,22 +490,90 @@ impl<'a> Graphics<'a> {
     }
 
     /// Load a custom shader from WGSL source code
-    pub fn load_shader(&mut self, wgsl_source: &str) -> usize {
+    ///
+    /// egor fills a globals block at `@group(1) @binding(0)` every frame, declare the
+    /// fields you need in order (a prefix is enough):
+    /// ```wgsl
+    /// struct Globals {
+    ///     view_proj: mat4x4<f32>,
+    ///     time: f32,             // seconds since start
+    ///     delta: f32,            // seconds since the previous frame
+    ///     frame: u32,
+    ///     zoom: f32,             // camera zoom, 1 in screen space
+    ///     resolution: vec2<f32>, // target or viewport size in pixels
+    ///     mouse: vec2<f32>,      // mouse in pixels of the target or viewport
+    /// }
+    /// @group(1) @binding(0) var<uniform> globals: Globals;
+    /// ```
+    /// The shader is validated first, errors carry the line, column & offending source line  
+    /// [`egor_render::Preprocessor`] adds `#include`, `#define` & a prelude declaring all of
+    /// the above plus `vs_main`, so an effect only needs `fs_main`
+    pub fn load_shader(&mut self, wgsl_source: &str) -> Result<usize, ShaderError> {
         self.renderer.add_shader(wgsl_source)
     }
 
-    /// Create a uniform buffer from raw bytes, returns a uniform id
-    pub fn create_uniform(&mut self, data: &[u8]) -> usize {
-        self.renderer.add_uniform(data)
+    /// Rebuild a loaded shader from new WGSL source, e.g. after editing it on disk
+    ///
+    /// If it fails the last good version keeps drawing, & debug builds show the error
+    /// on screen until a reload succeeds
+    pub fn reload_shader(
+        &mut self,
+        shader_id: usize,
+        wgsl_source: &str,
+    ) -> Result<(), ShaderError> {
+        self.renderer.reload_shader(shader_id, wgsl_source)
+    }
+
+    /// Draws failed shader reloads on top of the frame
+    #[cfg(debug_assertions)]
+    pub(crate) fn draw_shader_errors(&mut self) {
+        const TEXT_SIZE: f32 = 16.0;
+        const PADDING: f32 = 10.0;
+
+        let report = self
+            .renderer
+            .shader_errors()
+            .map(|(id, err)| format!("shader {id}: {err}"))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if report.is_empty() {
+            return;
+        }
+        let lines = report.lines().count() as f32;
+        let size = vec2(
+            self.screen_size().x,
+            lines * TEXT_SIZE * 1.2 + PADDING * 2.0,
+        );
+        self.screen_space(|gfx| {
+            gfx.rect()
+                .size(size)
+                .color(Color::new([0.3, 0.0, 0.0, 0.9]));
+        });
+        self.text(&report)
+            .at(Vec2::splat(PADDING))
+            .size(TEXT_SIZE)
+            .color(Color::WHITE);
     }
 
-    /// Update an existing uniform buffer with raw bytes
-    pub fn update_uniform(&mut self, id: usize, data: &[u8]) {
-        self.renderer.update_uniform(id, data);
+    /// Create a uniform buffer holding `value`, update it later with [`Uniform::set`]
+    ///
+    /// `T` must lay out like the WGSL struct, padded to its alignment (16 bytes for
+    /// `vec3`, `vec4` & nested structs)
+    pub fn create_uniform<T: Pod>(&mut self, value: &T) -> Uniform<T> {
+        self.renderer.create_uniform(value)
     }
 
-    /// Load a custom shader with associated uniform buffers
-    pub fn load_shader_with_uniforms(&mut self, wgsl_source: &str, uniform_ids: &[usize]) -> usize {
+    /// Load a custom shader with uniform buffers from [`Self::create_uniform`], bound from
+    /// `@group(2)` on in the order of `uniform_ids`
+    ///
+    /// Errors if the WGSL doesn't parse, an id isn't a uniform, or a buffer is missing or
+    /// sized differently than the uniform its group declares  
+    /// The globals described in [`Self::load_shader`] are available too
+    pub fn load_shader_with_uniforms(
+        &mut self,
+        wgsl_source: &str,
+        uniform_ids: &[usize],
+    ) -> Result<usize, ShaderError> {
         self.renderer
             .add_shader_with_uniforms(wgsl_source, uniform_ids)
     }
@@ -185,10 +581,116 @@ impl<'a> Graphics<'a> {
     /// Execute drawing commands with a custom shader
     ///
     /// The shader is automatically reset to default after the closure drops
-    pub fn with_shader(&mut self, shader_id: usize, mut render_fn: impl FnMut(&mut Self)) {
-        let previous_shader = self.current_shader;
+    pub fn with_shader(&mut self, shader_id: usize, render_fn: impl FnMut(&mut Self)) {
+        self.scoped_shader(shader_id, None, render_fn);
+    }
+
+    /// Load a custom shader reading a per-draw `T` at `@group(2)`, set with
+    /// [`Self::with_shader_params`], & uniform buffers bound from `@group(3)` on
+    ///
+    /// `T` follows the same layout rules as [`Self::create_uniform`] & is at most
+    /// [`egor_render::PARAMS_BLOCK_SIZE`] bytes  
+    /// Errors like [`Self::load_shader_with_uniforms`], or if `T` is too large
+    pub fn load_shader_with_params<T: Pod>(
+        &mut self,
+        wgsl_source: &str,
+        uniform_ids: &[usize],
+    ) -> Result<usize, ShaderError> {
+        self.renderer
+            .add_shader_with_params(wgsl_source, size_of::<T>() as u64, uniform_ids)
+    }
+
+    /// Load a custom shader with any mix of a per-draw parameter block, uniform buffers &
+    /// extra textures, assigned groups from `@group(2)` on in that order
+    ///
+    /// Each extra texture takes a group with the texture at `@binding(0)` & its sampler at
+    /// `@binding(1)`, pick the textures per draw with [`Self::with_textures`]:
+    /// ```wgsl
+    /// @group(2) @binding(0) var t_noise: texture_2d<f32>;
+    /// @group(2) @binding(1) var s_noise: sampler;
+    /// ```
+    /// Errors like [`Self::load_shader_with_uniforms`], or if the bindings need more groups
+    /// than the device has
+    pub fn load_shader_with_bindings(
+        &mut self,
+        wgsl_source: &str,
+        bindings: &ShaderBindings,
+    ) -> Result<usize, ShaderError> {
+        self.renderer
+            .add_shader_with_bindings(wgsl_source, bindings)
+    }
+
+    /// Load a custom shader from a file, run through [`egor_render::Preprocessor`] so it
+    /// only needs `fs_main` & can `#include` files next to it
+    ///
+    /// With the `hot_reload` feature the file & its includes are watched, & the shader is
+    /// rebuilt under the same id when one changes. Like [`Self::reload_shader`], a broken
+    /// edit keeps the last good version  
+    /// Errors point at the line in the file or include they came from. A file that fails
+    /// its first load has no id to rebuild & isn't watched, load it again once it changes
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_shader_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        bindings: &ShaderBindings,
+    ) -> Result<usize, AssetError> {
+        let path = path.as_ref();
+        let shader = assets::read_shader(path)?;
+        let id = self
+            .renderer
+            .add_shader_with_bindings(&shader.wgsl, bindings)
+            .map_err(|e| AssetError::Shader(path.to_path_buf(), shader.map.remap(e)))?;
+        #[cfg(feature = "hot_reload")]
+        if let Some(assets) = self.assets.as_deref_mut() {
+            assets.watch_shader(id, shader.files);
+        }
+        Ok(id)
+    }
+
+    /// Execute drawing commands sampling `texture_ids` in the extra texture groups of a
+    /// shader from [`Self::load_shader_with_bindings`]
+    ///
+    /// Groups without a texture sample the default white texture  
+    /// Panics if given more than [`MAX_SHADER_TEXTURES`] textures
+    pub fn with_textures(&mut self, texture_ids: &[usize], mut render_fn: impl FnMut(&mut Self)) {
+        assert!(
+            texture_ids.len() <= MAX_SHADER_TEXTURES,
+            "at most {MAX_SHADER_TEXTURES} extra shader textures are supported"
+        );
+        let previous = self.current_textures;
+        self.current_textures = [None; MAX_SHADER_TEXTURES];
+        for (slot, &id) in self.current_textures.iter_mut().zip(texture_ids) {
+            *slot = Some(id);
+        }
+        render_fn(self);
+        self.current_textures = previous;
+    }
+
+    /// Execute drawing commands with a shader from [`Self::load_shader_with_params`],
+    /// reading `params` for just these draws
+    ///
+    /// Each call gets its own parameter block, so the same shader can be drawn with
+    /// different values in one frame
+    pub fn with_shader_params<T: Pod>(
+        &mut self,
+        shader_id: usize,
+        params: &T,
+        render_fn: impl FnMut(&mut Self),
+    ) {
+        let offset = self.renderer.push_params(bytemuck::bytes_of(params));
+        self.scoped_shader(shader_id, Some(offset), render_fn);
+    }
+
+    fn scoped_shader(
+        &mut self,
+        shader_id: usize,
+        params: Option<u32>,
+        render_fn: impl FnOnce(&mut Self),
+    ) {
+        let previous = (self.current_shader, self.current_params);
         self.current_shader = Some(shader_id);
+        self.current_params = params;
         render_fn(self);
-        self.current_shader = previous_shader;
+        (self.current_shader, self.current_params) = previous;
     }
 }
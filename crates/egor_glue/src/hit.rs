@@ -0,0 +1,235 @@
+use egor_render::vertex::Vertex;
+use glam::Vec2;
+
+use crate::{graphics::CameraSlot, math::Rect};
+
+/// Triangulated outline of a primitive drawn with an id
+struct HitShape {
+    id: u64,
+    /// Camera slot the primitive was drawn with
+    camera: usize,
+    triangles: Vec<[Vec2; 3]>,
+    bounds: Rect,
+    /// Viewport the primitive is clipped to, in screen space after [`HitRegistry::finish`]
+    clip: Option<Rect>,
+}
+
+/// Outlines of primitives drawn with an id, in draw order
+///
+/// Outlines are recorded in world space while drawing & moved into screen
+/// space by [`Self::finish`], so queries work with pixel coordinates
+#[derive(Default)]
+pub(crate) struct HitRegistry {
+    shapes: Vec<HitShape>,
+}
+
+impl HitRegistry {
+    /// Records world-space triangles drawn with camera slot `camera` under `id`,
+    /// later records are on top
+    pub(crate) fn record(
+        &mut self,
+        id: u64,
+        camera: usize,
+        triangles: impl IntoIterator<Item = [Vec2; 3]>,
+    ) {
+        let triangles: Vec<[Vec2; 3]> = triangles.into_iter().collect();
+        if triangles.is_empty() {
+            return;
+        }
+        let bounds = Rect::from_points(triangles.iter().flatten().copied());
+        self.shapes.push(HitShape {
+            id,
+            camera,
+            triangles,
+            bounds,
+            clip: None,
+        });
+    }
+
+    /// Records a quad given by its corners in order under `id`
+    pub(crate) fn record_quad(&mut self, id: u64, camera: usize, corners: [Vec2; 4]) {
+        let [a, b, c, d] = corners;
+        self.record(id, camera, [[a, b, c], [c, d, a]]);
+    }
+
+    /// Moves all outlines into screen space using the camera & viewport of their slot
+    pub(crate) fn finish(&mut self, slots: &[CameraSlot]) {
+        for shape in &mut self.shapes {
+            let Some(CameraSlot { camera, viewport }) = slots.get(shape.camera) else {
+                continue;
+            };
+            for p in shape.triangles.iter_mut().flatten() {
+                *p = viewport.position + camera.world_to_screen(*p, viewport.size);
+            }
+            shape.bounds = Rect::from_points(shape.triangles.iter().flatten().copied());
+            shape.clip = Some(*viewport);
+        }
+    }
+
+    /// Unique ids of outlines containing `point`, topmost first
+    pub(crate) fn at(&self, point: Vec2) -> Vec<u64> {
+        self.query(|shape| {
+            shape.bounds.contains(point)
+                && shape.clip.is_none_or(|clip| clip.contains(point))
+                && shape.triangles.iter().any(|t| triangle_contains(t, point))
+        })
+    }
+
+    /// Unique ids of outlines overlapping `rect`, topmost first
+    pub(crate) fn in_rect(&self, rect: &Rect) -> Vec<u64> {
+        self.query(|shape| {
+            shape.bounds.intersects(rect)
+                && shape.clip.is_none_or(|clip| clip.intersects(rect))
+                && shape.triangles.iter().any(|t| triangle_overlaps(t, rect))
+        })
+    }
+
+    fn query(&self, hit: impl Fn(&HitShape) -> bool) -> Vec<u64> {
+        let mut ids = Vec::new();
+        for shape in self.shapes.iter().rev() {
+            if !ids.contains(&shape.id) && hit(shape) {
+                ids.push(shape.id);
+            }
+        }
+        ids
+    }
+}
+
+/// Triangles of indexed vertex data
+pub(crate) fn triangles<'a>(
+    vertices: &'a [Vertex],
+    indices: &'a [u16],
+) -> impl Iterator<Item = [Vec2; 3]> + 'a {
+    indices
+        .chunks_exact(3)
+        .map(|tri| std::array::from_fn(|i| vertices[tri[i] as usize].position.into()))
+}
+
+fn cross(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b - a).perp_dot(p - a)
+}
+
+/// Whether `p` is inside or on the edge of a triangle of either winding
+fn triangle_contains(t: &[Vec2; 3], p: Vec2) -> bool {
+    let d = [
+        cross(t[0], t[1], p),
+        cross(t[1], t[2], p),
+        cross(t[2], t[0], p),
+    ];
+    let negative = d.iter().any(|&d| d < 0.0);
+    let positive = d.iter().any(|&d| d > 0.0);
+    !(negative && positive)
+}
+
+/// Separating axis test between a triangle & an axis-aligned rectangle
+fn triangle_overlaps(t: &[Vec2; 3], rect: &Rect) -> bool {
+    if !Rect::from_points(*t).intersects(rect) {
+        return false;
+    }
+    let corners = rect.corners();
+    (0..3).all(|i| {
+        let axis = (t[(i + 1) % 3] - t[i]).perp();
+        let project = |p: &Vec2| axis.dot(*p);
+        let (t_min, t_max) = t
+            .iter()
+            .map(project)
+            .fold((f32::MAX, f32::MIN), |(lo, hi), d| (lo.min(d), hi.max(d)));
+        let (r_min, r_max) = corners
+            .iter()
+            .map(project)
+            .fold((f32::MAX, f32::MIN), |(lo, hi), d| (lo.min(d), hi.max(d)));
+        t_min <= r_max && r_min <= t_max
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use glam::vec2;
+
+    fn square(registry: &mut HitRegistry, id: u64, position: Vec2, size: f32) {
+        registry.record_quad(id, 0, Rect::new(position, Vec2::splat(size)).corners());
+    }
+
+    #[test]
+    fn topmost_first() {
+        let mut hits = HitRegistry::default();
+        square(&mut hits, 1, vec2(0.0, 0.0), 10.0);
+        square(&mut hits, 2, vec2(5.0, 5.0), 10.0);
+        square(&mut hits, 1, vec2(6.0, 6.0), 1.0);
+
+        assert_eq!(hits.at(vec2(6.5, 6.5)), [1, 2]);
+        assert_eq!(hits.at(vec2(12.0, 12.0)), [2]);
+        assert!(hits.at(vec2(20.0, 0.0)).is_empty());
+    }
+
+    #[test]
+    fn rotated_outline() {
+        // A diamond: its bounding box corners are outside the outline
+        let mut hits = HitRegistry::default();
+        hits.record_quad(
+            7,
+            0,
+            [
+                vec2(5.0, 0.0),
+                vec2(10.0, 5.0),
+                vec2(5.0, 10.0),
+                vec2(0.0, 5.0),
+            ],
+        );
+
+        assert_eq!(hits.at(vec2(5.0, 5.0)), [7]);
+        assert!(hits.at(vec2(1.0, 1.0)).is_empty());
+        assert!(
+            hits.in_rect(&Rect::new(vec2(0.0, 0.0), vec2(2.0, 2.0)))
+                .is_empty()
+        );
+        assert_eq!(
+            hits.in_rect(&Rect::new(vec2(3.0, 3.0), vec2(1.0, 1.0))),
+            [7]
+        );
+    }
+
+    #[test]
+    fn finish_applies_camera() {
+        let mut hits = HitRegistry::default();
+        square(&mut hits, 3, vec2(100.0, 100.0), 10.0);
+        hits.record_quad(4, 1, Rect::new(Vec2::ZERO, Vec2::splat(5.0)).corners());
+
+        let mut camera = Camera::default();
+        camera.target(vec2(100.0, 100.0));
+        camera.set_zoom(2.0);
+        let screen = Rect::new(Vec2::ZERO, vec2(800.0, 600.0));
+        hits.finish(&[
+            CameraSlot {
+                camera,
+                viewport: screen,
+            },
+            CameraSlot {
+                camera: Camera::default(),
+                viewport: screen,
+            },
+        ]);
+
+        assert_eq!(hits.at(vec2(19.0, 19.0)), [3]);
+        assert!(hits.at(vec2(21.0, 1.0)).is_empty());
+        // Slot 1 stays in screen space
+        assert_eq!(hits.at(vec2(4.0, 4.0)), [4, 3]);
+    }
+
+    #[test]
+    fn viewport_offsets_and_clips() {
+        let mut hits = HitRegistry::default();
+        square(&mut hits, 5, vec2(0.0, 0.0), 200.0);
+        hits.finish(&[CameraSlot {
+            camera: Camera::default(),
+            viewport: Rect::new(vec2(400.0, 0.0), vec2(100.0, 100.0)),
+        }]);
+
+        assert_eq!(hits.at(vec2(450.0, 50.0)), [5]);
+        assert!(hits.at(vec2(50.0, 50.0)).is_empty());
+        // The outline reaches past the viewport but is clipped to it
+        assert!(hits.at(vec2(550.0, 150.0)).is_empty());
+    }
+}
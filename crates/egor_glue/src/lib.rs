@@ -1,10 +1,23 @@
+pub mod animation;
 pub mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod assets;
 pub mod camera;
+pub mod camera_controller;
+pub mod camera_follow;
 pub mod color;
 pub mod graphics;
+mod hit;
 pub mod math;
+pub mod mesh;
 pub mod primitives;
+pub mod scaling;
+#[cfg(feature = "svg")]
+pub mod svg;
 pub mod text;
+#[cfg(any(feature = "json", feature = "tmx"))]
+pub mod tiled;
+pub mod tilemap;
 
 #[cfg(feature = "ui")]
 pub mod ui;
@@ -13,6 +13,17 @@ impl Rect {
         Self { position, size }
     }
 
+    /// Returns the smallest rectangle containing every point  
+    /// Returns a zero-sized rectangle at the origin if there are no points
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        let mut points = points.into_iter();
+        let Some(first) = points.next() else {
+            return Self::new(Vec2::ZERO, Vec2::ZERO);
+        };
+        let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+        Self::new(min, max - min)
+    }
+
     /// Returns the top-left corner (min coords)
     pub fn min(&self) -> Vec2 {
         self.position
@@ -38,6 +49,11 @@ impl Rect {
         point.cmpge(self.position).all() && point.cmple(self.position + self.size).all()
     }
 
+    /// Returns true if the rectangles overlap or touch
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min().cmple(other.max()).all() && other.min().cmple(self.max()).all()
+    }
+
     /// Returns the four corners in this order: top-left, top-right, bottom-right, bottom-left
     pub fn corners(&self) -> [Vec2; 4] {
         let tl = self.position;
@@ -75,6 +91,16 @@ mod tests {
         assert!(!r.contains(vec2(1.0, 2.1))); // outside top
     }
 
+    #[test]
+    fn intersects() {
+        // overlapping & touching rects intersect, separated ones don't
+        let r = Rect::new(vec2(0.0, 0.0), vec2(2.0, 2.0));
+        assert!(r.intersects(&Rect::new(vec2(1.0, 1.0), vec2(2.0, 2.0))));
+        assert!(r.intersects(&Rect::new(vec2(2.0, 0.0), vec2(1.0, 1.0)))); // shared edge
+        assert!(!r.intersects(&Rect::new(vec2(3.0, 0.0), vec2(1.0, 1.0))));
+        assert!(!r.intersects(&Rect::new(vec2(0.0, -2.0), vec2(1.0, 1.0))));
+    }
+
     #[test]
     fn corners() {
         // returns the 4 corners in TL, TR, BR, BL order
@@ -85,4 +111,13 @@ mod tests {
         assert_eq!(corners[2], vec2(2.0, 2.0)); // bottom-right
         assert_eq!(corners[3], vec2(0.0, 2.0)); // bottom-left
     }
+
+    #[test]
+    fn from_points() {
+        // bounding box of scattered points, empty input collapses to the origin
+        let r = Rect::from_points([vec2(1.0, -2.0), vec2(-3.0, 4.0), vec2(0.0, 0.0)]);
+        assert_eq!(r.min(), vec2(-3.0, -2.0));
+        assert_eq!(r.max(), vec2(1.0, 4.0));
+        assert_eq!(Rect::from_points([]), Rect::new(Vec2::ZERO, Vec2::ZERO));
+    }
 }
@@ -0,0 +1,268 @@
+use egor_render::vertex::Vertex;
+use glam::{Mat2, Vec2};
+use lyon::tessellation::VertexBuffers;
+
+use crate::{
+    color::Color,
+    hit::triangles,
+    math::Rect,
+    primitives::{PrimitiveBatch, transformed_bounds},
+};
+
+/// Pre-tessellated triangles that can be drawn many times without re-tessellating
+///
+/// Geometry is split into parts that each fit 16-bit indices
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    parts: Vec<VertexBuffers<Vertex, u16>>,
+    /// Bounds of all vertices, kept up to date by [`Self::push`]
+    bounds: Option<Rect>,
+}
+
+impl Mesh {
+    const MAX_VERTICES: usize = u16::MAX as usize;
+
+    /// Create an empty mesh
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append triangles, `indices` are relative to `vertices`
+    ///
+    /// More vertices than one part holds are split across parts triangle by triangle
+    pub fn push(&mut self, vertices: &[Vertex], indices: &[u16]) {
+        if vertices.is_empty() || indices.is_empty() {
+            return;
+        }
+
+        let added = Rect::from_points(vertices.iter().map(|v| v.position.into()));
+        self.bounds = Some(match self.bounds {
+            Some(b) => Rect::from_points([b.min(), b.max(), added.min(), added.max()]),
+            None => added,
+        });
+
+        if vertices.len() > Self::MAX_VERTICES {
+            self.push_split(vertices, indices);
+            return;
+        }
+
+        let fits = self
+            .parts
+            .last()
+            .is_some_and(|p| p.vertices.len() + vertices.len() <= Self::MAX_VERTICES);
+        if !fits {
+            self.parts.push(VertexBuffers::new());
+        }
+
+        let part = self.parts.last_mut().unwrap();
+        let base = part.vertices.len() as u16;
+        part.vertices.extend_from_slice(vertices);
+        part.indices.extend(indices.iter().map(|i| base + i));
+    }
+
+    /// Copies triangles into fresh parts, re-indexing the vertices each part uses
+    fn push_split(&mut self, vertices: &[Vertex], indices: &[u16]) {
+        let mut remap: Vec<Option<u16>> = vec![None; vertices.len()];
+        let mut part = VertexBuffers::new();
+        for triangle in indices.chunks_exact(3) {
+            if part.vertices.len() + 3 > Self::MAX_VERTICES {
+                self.parts
+                    .push(std::mem::replace(&mut part, VertexBuffers::new()));
+                remap.fill(None);
+            }
+            for &i in triangle {
+                let index = *remap[i as usize].get_or_insert_with(|| {
+                    part.vertices.push(vertices[i as usize]);
+                    (part.vertices.len() - 1) as u16
+                });
+                part.indices.push(index);
+            }
+        }
+        if !part.indices.is_empty() {
+            self.parts.push(part);
+        }
+    }
+
+    /// Total number of vertices
+    pub fn vertex_count(&self) -> usize {
+        self.parts.iter().map(|p| p.vertices.len()).sum()
+    }
+    /// Total number of indices
+    pub fn index_count(&self) -> usize {
+        self.parts.iter().map(|p| p.indices.len()).sum()
+    }
+    /// Whether the mesh has no triangles
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+    /// Iterates all vertices in mesh space
+    pub fn vertices(&self) -> impl Iterator<Item = &Vertex> {
+        self.parts.iter().flat_map(|p| &p.vertices)
+    }
+    /// Axis-aligned bounds of all vertices in mesh space
+    pub fn bounds(&self) -> Rect {
+        self.bounds.unwrap_or(Rect::new(Vec2::ZERO, Vec2::ZERO))
+    }
+}
+
+/// Builder for drawing a [`Mesh`], drawn on `Drop`
+pub struct MeshBuilder<'a> {
+    batch: &'a mut PrimitiveBatch,
+    shader_id: Option<usize>,
+    mesh: &'a Mesh,
+    position: Vec2,
+    rotation: f32,
+    scale: Vec2,
+    tint: Color,
+    tex_id: Option<usize>,
+    hit_id: Option<u64>,
+}
+
+impl<'a> MeshBuilder<'a> {
+    pub(crate) fn new(
+        batch: &'a mut PrimitiveBatch,
+        shader_id: Option<usize>,
+        mesh: &'a Mesh,
+    ) -> Self {
+        Self {
+            batch,
+            shader_id,
+            mesh,
+            position: Vec2::ZERO,
+            rotation: 0.0,
+            scale: Vec2::ONE,
+            tint: Color::WHITE,
+            tex_id: None,
+            hit_id: None,
+        }
+    }
+
+    /// Sets the world-space position of the mesh origin
+    pub fn at(mut self, pos: Vec2) -> Self {
+        self.position = pos;
+        self
+    }
+    /// Sets rotation in radians around the mesh origin
+    pub fn rotate(mut self, angle: f32) -> Self {
+        self.rotation = angle;
+        self
+    }
+    /// Sets the scale of the mesh
+    pub fn scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+    /// Multiplies every vertex color by `color`
+    pub fn tint(mut self, color: Color) -> Self {
+        self.tint = color;
+        self
+    }
+    /// Sets the texture ID sampled with the mesh's texture coordinates
+    pub fn texture(mut self, id: usize) -> Self {
+        self.tex_id = Some(id);
+        self
+    }
+    /// Records the drawn outline under `id` for hit testing
+    pub fn id(mut self, id: u64) -> Self {
+        self.hit_id = Some(id);
+        self
+    }
+}
+
+impl Drop for MeshBuilder<'_> {
+    fn drop(&mut self) {
+        let rot = Mat2::from_angle(self.rotation);
+        let bounds = transformed_bounds(&self.mesh.bounds(), self.scale, rot, self.position);
+        if !self.batch.is_visible(&bounds) {
+            return;
+        }
+        let tint = self.tint.components();
+
+        for part in &self.mesh.parts {
+            let Some((verts, indices, base)) = self.batch.allocate(
+                part.vertices.len(),
+                part.indices.len(),
+                self.tex_id,
+                self.shader_id,
+            ) else {
+                continue;
+            };
+
+            for (vo, vi) in verts.iter_mut().zip(&part.vertices) {
+                let p = rot * (self.scale * Vec2::from(vi.position)) + self.position;
+                *vo = Vertex {
+                    position: p.to_array(),
+                    color: std::array::from_fn(|i| vi.color[i] * tint[i]),
+                    tex_coords: vi.tex_coords,
+                };
+            }
+
+            for (i, idx) in indices.iter_mut().zip(&part.indices) {
+                *i = base + idx;
+            }
+
+            if let Some(id) = self.hit_id {
+                let outline: Vec<_> = triangles(verts, &part.indices).collect();
+                self.batch.record_hit(id, outline);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad() -> ([Vertex; 4], [u16; 6]) {
+        let v = |x, y| Vertex::new([x, y], [1.0; 4], [0.0; 2]);
+        (
+            [v(0., 0.), v(1., 0.), v(1., 1.), v(0., 1.)],
+            [0, 1, 2, 0, 2, 3],
+        )
+    }
+
+    #[test]
+    fn push_rebases_indices() {
+        let (verts, idx) = quad();
+        let mut mesh = Mesh::new();
+        mesh.push(&verts, &idx);
+        mesh.push(&verts, &idx);
+
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.parts[0].indices[6..], [4, 5, 6, 4, 6, 7]);
+    }
+
+    #[test]
+    fn push_splits_past_u16_range() {
+        let (verts, idx) = quad();
+        let mut mesh = Mesh::new();
+        for _ in 0..(Mesh::MAX_VERTICES / 4 + 1) {
+            mesh.push(&verts, &idx);
+        }
+
+        assert_eq!(mesh.parts.len(), 2);
+        assert_eq!(mesh.parts[1].indices, idx);
+    }
+
+    #[test]
+    fn single_push_splits_past_u16_range() {
+        let v = |x: u16| Vertex::new([x as f32, 0.0], [1.0; 4], [0.0; 2]);
+        let verts: Vec<_> = (0..=u16::MAX).map(v).collect();
+        // a strip of triangles reaching the last vertex
+        let idx: Vec<u16> = (0..u16::MAX - 1).flat_map(|i| [i, i + 1, i + 2]).collect();
+        let mut mesh = Mesh::new();
+        mesh.push(&verts, &idx);
+
+        assert_eq!(mesh.parts.len(), 2);
+        assert_eq!(mesh.index_count(), idx.len());
+        for part in &mesh.parts {
+            assert!(part.vertices.len() <= Mesh::MAX_VERTICES);
+            assert!(
+                part.indices
+                    .iter()
+                    .all(|&i| (i as usize) < part.vertices.len())
+            );
+        }
+        assert_eq!(mesh.bounds().max().x, u16::MAX as f32);
+    }
+}
@@ -1,29 +1,68 @@
-use crate::{color::Color, math::Rect};
-use egor_render::{GeometryBatch, vertex::Vertex};
+use crate::{
+    color::Color,
+    graphics::CullStats,
+    hit::{HitRegistry, triangles},
+    math::Rect,
+};
+use egor_render::{DrawParams, GeometryBatch, MAX_SHADER_TEXTURES, Renderer, vertex::Vertex};
 use glam::{Mat2, Vec2, vec2};
+use lyon::algorithms::aabb::fast_bounding_box;
 use lyon::geom::euclid::Point2D;
 use lyon::geom::{Box2D, Point};
-use lyon::path::Winding;
-use lyon::math::point;
+use lyon::math::{Angle, point, vector};
 use lyon::path::Path;
+use lyon::path::Winding;
 use lyon::tessellation::*;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
 
 const MIN_THICKNESS: f32 = 0.001;
+/// Most quads one batch can index with 16-bit indices
+const MAX_BATCH_QUADS: usize = u16::MAX as usize / 4;
 
 #[derive(Default)]
 struct BatchEntry {
-    texture_id: Option<usize>,
-    shader_id: Option<usize>,
+    params: DrawParams,
     geometry: GeometryBatch,
 }
 
-#[derive(Default)]
 pub(crate) struct PrimitiveBatch {
     batches: Vec<BatchEntry>,
+    /// Zoom of the active camera (screen pixels per world unit), synced by [`crate::graphics::Graphics`]
+    pub(crate) zoom: f32,
+    /// Camera slot new geometry is drawn with, synced by [`crate::graphics::Graphics`]
+    pub(crate) camera: usize,
+    /// Shader parameter block new geometry is drawn with, synced by [`crate::graphics::Graphics`]
+    pub(crate) params: Option<u32>,
+    /// Extra shader textures new geometry is drawn with, synced by [`crate::graphics::Graphics`]
+    pub(crate) extra_textures: [Option<usize>; MAX_SHADER_TEXTURES],
+    /// Outlines of primitives drawn with an id
+    pub(crate) hits: HitRegistry,
+    /// Area visible through the active camera in its space, `None` draws everything.
+    /// Synced by [`crate::graphics::Graphics`]
+    pub(crate) visible: Option<Rect>,
+    /// Draws submitted & culled so far
+    pub(crate) cull_stats: CullStats,
+}
+
+impl Default for PrimitiveBatch {
+    fn default() -> Self {
+        Self {
+            batches: Vec::new(),
+            zoom: 1.0,
+            camera: 0,
+            params: None,
+            extra_textures: [None; MAX_SHADER_TEXTURES],
+            hits: HitRegistry::default(),
+            visible: None,
+            cull_stats: CullStats::default(),
+        }
+    }
 }
 
 impl PrimitiveBatch {
     /// Allocates space for vertices & indices in the correct batch for `texture_id` + `shader_id`
+    /// & the current camera, parameter block & extra textures
     pub(crate) fn allocate(
         &mut self,
         vert_count: usize,
@@ -31,17 +70,37 @@ impl PrimitiveBatch {
         texture_id: Option<usize>,
         shader_id: Option<usize>,
     ) -> Option<(&mut [Vertex], &mut [u16], u16)> {
-        if let Some(i) = self.batches.iter().position(|e| {
-            e.texture_id == texture_id
-                && e.shader_id == shader_id
-                && !e.geometry.would_overflow(vert_count, idx_count)
-        }) {
+        self.allocate_sampled(vert_count, idx_count, texture_id, false, shader_id)
+    }
+
+    /// Like [`Self::allocate`], sampling the texture with wrapping UVs when `repeat` is set
+    fn allocate_sampled(
+        &mut self,
+        vert_count: usize,
+        idx_count: usize,
+        texture_id: Option<usize>,
+        repeat: bool,
+        shader_id: Option<usize>,
+    ) -> Option<(&mut [Vertex], &mut [u16], u16)> {
+        let params = DrawParams {
+            texture_id,
+            repeat,
+            shader_id,
+            camera: self.camera,
+            viewport: None,
+            params: self.params,
+            extra_textures: self.extra_textures,
+        };
+        if let Some(i) = self
+            .batches
+            .iter()
+            .position(|e| e.params == params && !e.geometry.would_overflow(vert_count, idx_count))
+        {
             return self.batches[i].geometry.try_allocate(vert_count, idx_count);
         }
 
         self.batches.push(BatchEntry {
-            texture_id,
-            shader_id,
+            params,
             geometry: GeometryBatch::default(),
         });
         self.batches
@@ -51,46 +110,143 @@ impl PrimitiveBatch {
             .try_allocate(vert_count, idx_count)
     }
 
-    pub(crate) fn take(&mut self) -> Vec<(Option<usize>, Option<usize>, GeometryBatch)> {
+    /// Copies finished world-space geometry into the batch for `texture_id` + `shader_id`
+    pub(crate) fn push(
+        &mut self,
+        geometry: &VertexBuffers<Vertex, u16>,
+        texture_id: Option<usize>,
+        shader_id: Option<usize>,
+    ) {
+        self.push_sampled(geometry, texture_id, false, shader_id);
+    }
+
+    /// Like [`Self::push`], sampling the texture with wrapping UVs when `repeat` is set
+    pub(crate) fn push_sampled(
+        &mut self,
+        geometry: &VertexBuffers<Vertex, u16>,
+        texture_id: Option<usize>,
+        repeat: bool,
+        shader_id: Option<usize>,
+    ) {
+        if geometry.indices.is_empty() {
+            return;
+        }
+        if let Some((verts, indices, base)) = self.allocate_sampled(
+            geometry.vertices.len(),
+            geometry.indices.len(),
+            texture_id,
+            repeat,
+            shader_id,
+        ) {
+            verts.copy_from_slice(&geometry.vertices);
+            for (i, idx) in indices.iter_mut().zip(&geometry.indices) {
+                *i = base + idx;
+            }
+        }
+    }
+
+    /// Counts a draw & returns whether its world-space `bounds` may be visible,
+    /// builders skip tessellation & allocation when it isn't
+    pub(crate) fn is_visible(&mut self, bounds: &Rect) -> bool {
+        self.cull_stats.submitted += 1;
+        let visible = self.visible.is_none_or(|v| v.intersects(bounds));
+        if !visible {
+            self.cull_stats.culled += 1;
+        }
+        visible
+    }
+
+    /// Records a world-space outline drawn with the current camera for hit testing
+    pub(crate) fn record_hit(&mut self, id: u64, triangles: impl IntoIterator<Item = [Vec2; 3]>) {
+        self.hits.record(id, self.camera, triangles);
+    }
+    /// Records a quad outline drawn with the current camera for hit testing
+    pub(crate) fn record_hit_quad(&mut self, id: u64, corners: [Vec2; 4]) {
+        self.hits.record_quad(id, self.camera, corners);
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<(DrawParams, GeometryBatch)> {
         std::mem::take(&mut self.batches)
             .into_iter()
-            .map(|entry| (entry.texture_id, entry.shader_id, entry.geometry))
+            .map(|entry| (entry.params, entry.geometry))
             .collect()
     }
 }
 
 /// Common anchor options
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Anchor {
-    Center,
+    #[default]
     TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    /// The anchor as a normalized pivot, (0, 0) top-left to (1, 1) bottom-right
+    pub fn pivot(self) -> Vec2 {
+        match self {
+            Anchor::TopLeft => vec2(0.0, 0.0),
+            Anchor::TopCenter => vec2(0.5, 0.0),
+            Anchor::TopRight => vec2(1.0, 0.0),
+            Anchor::CenterLeft => vec2(0.0, 0.5),
+            Anchor::Center => vec2(0.5, 0.5),
+            Anchor::CenterRight => vec2(1.0, 0.5),
+            Anchor::BottomLeft => vec2(0.0, 1.0),
+            Anchor::BottomCenter => vec2(0.5, 1.0),
+            Anchor::BottomRight => vec2(1.0, 1.0),
+        }
+    }
 }
 
 /// Builder for (textured) rectangles, drawn on `Drop`
 pub struct RectangleBuilder<'a> {
     batch: &'a mut PrimitiveBatch,
+    renderer: &'a Renderer,
     shader_id: Option<usize>,
-    anchor: Anchor,
+    pivot: Vec2,
+    rotate_around_pivot: bool,
     position: Vec2,
     size: Vec2,
     rotation: f32,
     color: Color,
     uvs: [[f32; 2]; 4],
+    source: Option<Rect>,
+    flip_x: bool,
+    flip_y: bool,
     tex_id: Option<usize>,
+    hit_id: Option<u64>,
 }
 
 /// Builds a rectangle with configurable position, size, color, anchor, rotation, & texture
 impl<'a> RectangleBuilder<'a> {
-    pub(crate) fn new(batch: &'a mut PrimitiveBatch, shader_id: Option<usize>) -> Self {
+    pub(crate) fn new(
+        batch: &'a mut PrimitiveBatch,
+        renderer: &'a Renderer,
+        shader_id: Option<usize>,
+    ) -> Self {
         Self {
             batch,
+            renderer,
             shader_id,
-            anchor: Anchor::TopLeft,
+            pivot: Anchor::TopLeft.pivot(),
+            rotate_around_pivot: false,
             position: Vec2::ZERO,
             size: vec2(64.0, 64.0),
             rotation: 0.0,
             color: Color::WHITE,
             uvs: [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            source: None,
+            flip_x: false,
+            flip_y: false,
             tex_id: None,
+            hit_id: None,
         }
     }
     /// Sets the position & size from a [`Rect`].
@@ -100,9 +256,17 @@ impl<'a> RectangleBuilder<'a> {
         self
     }
     /// Sets the anchor point of the rectangle  
-    /// Defaults to [`Anchor::TopLeft`].
+    /// Defaults to [`Anchor::TopLeft`]. Rotation stays around the center
     pub fn anchor(mut self, anchor: Anchor) -> Self {
-        self.anchor = anchor;
+        self.pivot = anchor.pivot();
+        self.rotate_around_pivot = false;
+        self
+    }
+    /// Sets a normalized pivot, (0, 0) top-left to (1, 1) bottom-right  
+    /// The pivot is placed at the position & the rectangle rotates around it
+    pub fn pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = pivot;
+        self.rotate_around_pivot = true;
         self
     }
     /// Sets the world-space position of the rectangle
@@ -135,30 +299,72 @@ impl<'a> RectangleBuilder<'a> {
     /// Defaults to covering the full texture ((0,0) - (1,1))
     pub fn uv(mut self, coords: [[f32; 2]; 4]) -> Self {
         self.uvs = coords;
+        self.source = None;
+        self
+    }
+    /// Samples a region of the texture given in pixels, e.g. a frame of a sprite sheet  
+    /// Overrides [`Self::uv`], ignored without a [`Self::texture`]
+    pub fn source_rect(mut self, rect: Rect) -> Self {
+        self.source = Some(rect);
+        self
+    }
+    /// Mirrors the texture horizontally
+    pub fn flip_x(mut self) -> Self {
+        self.flip_x = true;
+        self
+    }
+    /// Mirrors the texture vertically
+    pub fn flip_y(mut self) -> Self {
+        self.flip_y = true;
+        self
+    }
+    /// Mirrors the texture on each axis that is set, e.g. `.flip(facing_left, false)`
+    pub fn flip(mut self, x: bool, y: bool) -> Self {
+        self.flip_x = x;
+        self.flip_y = y;
+        self
+    }
+    /// Records the drawn outline under `id` for hit testing
+    pub fn id(mut self, id: u64) -> Self {
+        self.hit_id = Some(id);
         self
     }
 }
 
 impl Drop for RectangleBuilder<'_> {
     fn drop(&mut self) {
-        let offset = match self.anchor {
-            Anchor::TopLeft => Vec2::ZERO,
-            Anchor::Center => -self.size / 2.0,
-        };
-
-        let top_left = self.position + offset;
+        let top_left = self.position - self.pivot * self.size;
         let rect = Rect::new(top_left, self.size);
         let rot = Mat2::from_angle(self.rotation);
 
         let corners = rect.corners();
-        let center = rect.center();
+        let origin = if self.rotate_around_pivot {
+            self.position
+        } else {
+            rect.center()
+        };
+        let world = corners.map(|c| rot * (c - origin) + origin);
+        if !self.batch.is_visible(&Rect::from_points(world)) {
+            return;
+        }
         let color = self.color.components();
 
+        let mut uvs = self.uvs;
+        if let Some(source) = self.source {
+            let texture_size = self.tex_id.and_then(|id| self.renderer.texture_size(id));
+            if let Some((w, h)) = texture_size {
+                uvs = source_uvs(&source, vec2(w as f32, h as f32));
+            }
+        }
+        let uvs = flip_uvs(uvs, self.flip_x, self.flip_y);
+
+        if let Some(id) = self.hit_id {
+            self.batch.record_hit_quad(id, world);
+        }
         if let Some((verts, indices, base)) = self.batch.allocate(4, 6, self.tex_id, self.shader_id)
         {
             for i in 0..4 {
-                let world = rot * (corners[i] - center) + center;
-                verts[i] = Vertex::new(world.into(), color, self.uvs[i]);
+                verts[i] = Vertex::new(world[i].into(), color, uvs[i]);
             }
 
             indices.copy_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
@@ -166,16 +372,321 @@ impl Drop for RectangleBuilder<'_> {
     }
 }
 
+/// Normalized UVs (TL, TR, BR, BL) for a pixel `source` region of a texture of `texture_size`
+pub(crate) fn source_uvs(source: &Rect, texture_size: Vec2) -> [[f32; 2]; 4] {
+    let size = texture_size.max(Vec2::ONE);
+    let uv_rect = Rect::new(source.position / size, source.size / size);
+    uv_rect.corners().map(|c| c.to_array())
+}
+
+/// Mirrors UV corners (TL, TR, BR, BL) horizontally and/or vertically
+pub(crate) fn flip_uvs(uvs: [[f32; 2]; 4], flip_x: bool, flip_y: bool) -> [[f32; 2]; 4] {
+    let [mut tl, mut tr, mut br, mut bl] = uvs;
+    if flip_x {
+        (tl, tr, br, bl) = (tr, tl, bl, br);
+    }
+    if flip_y {
+        (tl, tr, br, bl) = (bl, br, tr, tl);
+    }
+    [tl, tr, br, bl]
+}
+
+/// Builder for nine-slice (nine-patch) rectangles, drawn on `Drop`
+///
+/// Corners keep their size, edges stretch along one axis & the center stretches
+/// along both. Edges & center can tile instead of stretching
+pub struct NineSliceBuilder<'a> {
+    batch: &'a mut PrimitiveBatch,
+    renderer: &'a Renderer,
+    shader_id: Option<usize>,
+    tex_id: usize,
+    borders: [f32; 4],
+    border_scale: f32,
+    pivot: Vec2,
+    position: Vec2,
+    size: Vec2,
+    color: Color,
+    tile_edges: bool,
+    tile_center: bool,
+    hit_id: Option<u64>,
+}
+
+impl<'a> NineSliceBuilder<'a> {
+    pub(crate) fn new(
+        batch: &'a mut PrimitiveBatch,
+        renderer: &'a Renderer,
+        shader_id: Option<usize>,
+        tex_id: usize,
+    ) -> Self {
+        Self {
+            batch,
+            renderer,
+            shader_id,
+            tex_id,
+            borders: [0.0; 4],
+            border_scale: 1.0,
+            pivot: Anchor::TopLeft.pivot(),
+            position: Vec2::ZERO,
+            size: vec2(64.0, 64.0),
+            color: Color::WHITE,
+            tile_edges: false,
+            tile_center: false,
+            hit_id: None,
+        }
+    }
+    /// Sets the left, top, right & bottom borders in texture pixels
+    pub fn borders(mut self, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        self.borders = [left, top, right, bottom].map(|b| b.max(0.0));
+        self
+    }
+    /// Scales borders from texture pixels to world units, e.g. for upscaled pixel art  
+    /// Borders shrink further when the size is too small to fit them
+    pub fn border_scale(mut self, scale: f32) -> Self {
+        self.border_scale = scale.max(0.0);
+        self
+    }
+    /// Sets the position & size from a [`Rect`]
+    pub fn with(mut self, rect: &Rect) -> Self {
+        self.position = rect.position;
+        self.size = rect.size;
+        self
+    }
+    /// Sets the anchor point  
+    /// Defaults to [`Anchor::TopLeft`]
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.pivot = anchor.pivot();
+        self
+    }
+    /// Sets the world-space position
+    pub fn at(mut self, position: impl Into<Vec2>) -> Self {
+        self.position = position.into();
+        self
+    }
+    /// Sets the total size, borders included
+    pub fn size(mut self, size: Vec2) -> Self {
+        self.size = size;
+        self
+    }
+    /// Sets the tint color
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    /// Tiles the edges instead of stretching them
+    pub fn tile_edges(mut self, tile: bool) -> Self {
+        self.tile_edges = tile;
+        self
+    }
+    /// Tiles the center instead of stretching it
+    pub fn tile_center(mut self, tile: bool) -> Self {
+        self.tile_center = tile;
+        self
+    }
+    /// Records the drawn outline under `id` for hit testing
+    pub fn id(mut self, id: u64) -> Self {
+        self.hit_id = Some(id);
+        self
+    }
+}
+
+impl Drop for NineSliceBuilder<'_> {
+    fn drop(&mut self) {
+        let Some((w, h)) = self.renderer.texture_size(self.tex_id) else {
+            return;
+        };
+        let dest = Rect::new(self.position - self.pivot * self.size, self.size);
+        if !self.batch.is_visible(&dest) {
+            return;
+        }
+        if let Some(id) = self.hit_id {
+            self.batch.record_hit_quad(id, dest.corners());
+        }
+        let quads = nine_slice_quads(
+            &dest,
+            vec2(w as f32, h as f32),
+            self.borders,
+            self.border_scale,
+            self.tile_edges,
+            self.tile_center,
+        );
+        let color = self.color.components();
+
+        // tiling a large dest can need more quads than one batch indexes
+        for chunk in quads.chunks(MAX_BATCH_QUADS) {
+            let Some((verts, indices, base)) = self.batch.allocate(
+                chunk.len() * 4,
+                chunk.len() * 6,
+                Some(self.tex_id),
+                self.shader_id,
+            ) else {
+                return;
+            };
+            for (q, (rect, uv)) in chunk.iter().enumerate() {
+                let uvs = uv.corners();
+                for (c, corner) in rect.corners().into_iter().enumerate() {
+                    verts[q * 4 + c] = Vertex::new(corner.into(), color, uvs[c].into());
+                }
+                let b = base + q as u16 * 4;
+                indices[q * 6..q * 6 + 6].copy_from_slice(&[b, b + 1, b + 2, b + 2, b + 3, b]);
+            }
+        }
+    }
+}
+
+/// Splits `dest` into nine-slice quads, returning (world rect, normalized uv rect) pairs
+///
+/// `borders` are left, top, right, bottom in pixels of a texture of `texture_size`
+fn nine_slice_quads(
+    dest: &Rect,
+    texture_size: Vec2,
+    borders: [f32; 4],
+    border_scale: f32,
+    tile_edges: bool,
+    tile_center: bool,
+) -> Vec<(Rect, Rect)> {
+    let [l, t, r, b] = borders;
+    let tex = texture_size.max(Vec2::ONE);
+    let size = dest.size.abs();
+
+    // Shrink the borders if they don't fit
+    let mut scale = border_scale;
+    if l + r > 0.0 {
+        scale = scale.min(size.x / (l + r));
+    }
+    if t + b > 0.0 {
+        scale = scale.min(size.y / (t + b));
+    }
+
+    // (world start, world length, source start, source length) per column & row
+    let columns = [
+        (0.0, l * scale, 0.0, l),
+        (l * scale, size.x - (l + r) * scale, l, tex.x - l - r),
+        (size.x - r * scale, r * scale, tex.x - r, r),
+    ];
+    let rows = [
+        (0.0, t * scale, 0.0, t),
+        (t * scale, size.y - (t + b) * scale, t, tex.y - t - b),
+        (size.y - b * scale, b * scale, tex.y - b, b),
+    ];
+
+    let mut quads = Vec::new();
+    for (row, &(y, height, sy, sh)) in rows.iter().enumerate() {
+        for (col, &(x, width, sx, sw)) in columns.iter().enumerate() {
+            if width <= 0.0 || height <= 0.0 || sw <= 0.0 || sh <= 0.0 {
+                continue;
+            }
+            let tile = match (row, col) {
+                (1, 1) => tile_center,
+                (1, _) | (_, 1) => tile_edges,
+                _ => false,
+            };
+            let x_spans = spans(width, (tile && col == 1).then_some(sw * scale));
+            let y_spans = spans(height, (tile && row == 1).then_some(sh * scale));
+
+            for &(oy, lh, fy) in &y_spans {
+                for &(ox, lw, fx) in &x_spans {
+                    let world = Rect::new(dest.position + vec2(x + ox, y + oy), vec2(lw, lh));
+                    let uv = Rect::new(vec2(sx, sy) / tex, vec2(sw * fx, sh * fy) / tex);
+                    quads.push((world, uv));
+                }
+            }
+        }
+    }
+    quads
+}
+
+/// Splits `len` into (offset, length, fraction of the source used) spans of `tile`,
+/// cropping the last one, or a single stretched span without tiling
+fn spans(len: f32, tile: Option<f32>) -> Vec<(f32, f32, f32)> {
+    // Very small tiles would explode the quad count, stretch those instead
+    let Some(tile) = tile.filter(|&t| t > 0.0 && len / t <= 1024.0) else {
+        return vec![(0.0, len, 1.0)];
+    };
+
+    let mut spans = Vec::new();
+    let mut offset = 0.0;
+    while len - offset > 1e-4 {
+        let span = tile.min(len - offset);
+        spans.push((offset, span, span / tile));
+        offset += tile;
+    }
+    spans
+}
+
+/// Rule deciding which regions of overlapping or self-intersecting outlines are filled
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FillRule {
+    /// Fills regions with a non-zero winding number (holes need opposite winding)
+    #[default]
+    NonZero,
+    /// Fills regions crossed an odd number of times (holes work regardless of winding)
+    EvenOdd,
+}
+
+impl From<FillRule> for lyon::tessellation::FillRule {
+    fn from(value: FillRule) -> Self {
+        match value {
+            FillRule::NonZero => Self::NonZero,
+            FillRule::EvenOdd => Self::EvenOdd,
+        }
+    }
+}
+
+/// How texture coordinates are generated for [`PolygonBuilder`] & [`ShapeBuilder`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum UvMode {
+    /// Stretches the texture over the bounding box of the outline
+    #[default]
+    Fit,
+    /// Repeats the texture in world space, once every `size` world units, shifted by `offset`
+    ///
+    /// Only these draws sample with wrapping UVs, every other draw clamps to the texture's edges
+    Tile { size: Vec2, offset: Vec2 },
+    /// Explicit per-point coordinates, matching [`PolygonBuilder::points`] followed by
+    /// the points of each hole in order
+    ///
+    /// Only supported by [`PolygonBuilder`], other builders fall back to [`UvMode::Fit`]
+    Explicit(Vec<[f32; 2]>),
+}
+
+impl UvMode {
+    /// Whether the generated coordinates leave 0..1 & need a repeating sampler
+    fn repeats(&self) -> bool {
+        matches!(self, UvMode::Tile { .. })
+    }
+
+    /// Generates the UV for a vertex from its local (untransformed) & world position
+    fn generate(&self, local: Vec2, world: Vec2, bounds: &Rect) -> [f32; 2] {
+        match self {
+            UvMode::Tile { size, offset } => ((world - *offset) / *size).into(),
+            UvMode::Fit | UvMode::Explicit(_) => {
+                ((local - bounds.position) / bounds.size.max(Vec2::splat(f32::EPSILON))).into()
+            }
+        }
+    }
+}
+
 /// Builder for polygons, triangles, circles, n-gons. Drawn on `Drop`
+///
+/// Explicit points are tessellated, so concave & self-intersecting outlines
+/// (and outlines with holes) fill correctly. Regular polygons & outlines marked
+/// with [`Self::convex`] take a cheaper triangle fan path
 pub struct PolygonBuilder<'a> {
     batch: &'a mut PrimitiveBatch,
     shader_id: Option<usize>,
     position: Vec2,
     rotation: f32,
     points: Vec<Vec2>,
+    holes: Vec<Vec<Vec2>>,
+    fill_rule: FillRule,
+    convex: bool,
+    antialias: bool,
     radius: f32,
     segments: usize,
     color: Color,
+    tex_id: Option<usize>,
+    uv_mode: UvMode,
+    hit_id: Option<u64>,
 }
 
 impl<'a> PolygonBuilder<'a> {
@@ -186,9 +697,16 @@ impl<'a> PolygonBuilder<'a> {
             position: Vec2::ZERO,
             rotation: 0.0,
             points: Vec::new(),
+            holes: Vec::new(),
+            fill_rule: FillRule::NonZero,
+            convex: false,
+            antialias: false,
             radius: 10.0,
             segments: 3,
             color: Color::WHITE,
+            tex_id: None,
+            uv_mode: UvMode::Fit,
+            hit_id: None,
         }
     }
     /// Sets the world-space position of the polygon
@@ -207,6 +725,33 @@ impl<'a> PolygonBuilder<'a> {
         self.points.extend_from_slice(pts);
         self
     }
+    /// Cuts a hole out of the polygon, in the same space as [`Self::points`]
+    ///
+    /// Can be called multiple times. With [`FillRule::NonZero`] holes must wind
+    /// opposite to the outer outline
+    pub fn hole(mut self, pts: &[Vec2]) -> Self {
+        self.holes.push(pts.to_vec());
+        self
+    }
+    /// Sets the fill rule used for self-intersecting outlines & holes  
+    /// Defaults to [`FillRule::NonZero`]
+    pub fn fill_rule(mut self, rule: FillRule) -> Self {
+        self.fill_rule = rule;
+        self
+    }
+    /// Marks the explicit points as a convex outline
+    ///
+    /// Skips tessellation & uses a triangle fan, which is faster but
+    /// renders incorrectly if the outline is actually concave. Ignored when holes are set
+    pub fn convex(mut self) -> Self {
+        self.convex = true;
+        self
+    }
+    /// Feathers the outer edges over 1 screen pixel for smooth edges without MSAA
+    pub fn antialias(mut self, enabled: bool) -> Self {
+        self.antialias = enabled;
+        self
+    }
     /// Set radius for a circle or regular n-gon
     pub fn radius(mut self, r: f32) -> Self {
         self.radius = r;
@@ -222,47 +767,157 @@ impl<'a> PolygonBuilder<'a> {
         self.color = color;
         self
     }
+    /// Sets the texture ID for the polygon
+    pub fn texture(mut self, id: usize) -> Self {
+        self.tex_id = Some(id);
+        self
+    }
+    /// Sets how texture coordinates are generated  
+    /// Defaults to [`UvMode::Fit`]
+    pub fn uv_mode(mut self, mode: UvMode) -> Self {
+        self.uv_mode = mode;
+        self
+    }
+    /// Records the drawn outline under `id` for hit testing
+    pub fn id(mut self, id: u64) -> Self {
+        self.hit_id = Some(id);
+        self
+    }
 }
 
 impl Drop for PolygonBuilder<'_> {
     fn drop(&mut self) {
-        let points: Vec<Vec2> = if !self.points.is_empty() {
-            self.points.clone()
+        let rot = Mat2::from_angle(self.rotation);
+        let center = self.position;
+        let local = if self.points.is_empty() {
+            Rect::new(Vec2::splat(-self.radius), Vec2::splat(self.radius * 2.0))
+        } else {
+            Rect::from_points(self.points.iter().copied())
+        };
+        if !self
+            .batch
+            .is_visible(&transformed_bounds(&local, Vec2::ONE, rot, center))
+        {
+            return;
+        }
+        let color = self.color.components();
+        let explicit_uvs = match &self.uv_mode {
+            UvMode::Explicit(uvs) => Some(uvs.as_slice()),
+            _ => None,
+        };
+
+        let geometry = if !self.points.is_empty() && (!self.convex || !self.holes.is_empty()) {
+            match fill_polygon(&self.points, &self.holes, self.fill_rule, explicit_uvs) {
+                Some(geometry) => geometry,
+                None => return,
+            }
+        } else if !self.points.is_empty() {
+            fan_polygon(&self.points, explicit_uvs)
         } else {
             let r = self.radius;
-            (0..self.segments)
+            let points: Vec<Vec2> = (0..self.segments)
                 .map(|i| {
-                    let t = i as f32 / self.segments as f32 * std::f32::consts::TAU;
+                    let t = i as f32 / self.segments as f32 * TAU;
                     Vec2::new(t.cos(), t.sin()) * r
                 })
-                .collect()
+                .collect();
+            fan_polygon(&points, explicit_uvs)
         };
 
-        let rot = Mat2::from_angle(self.rotation);
-        let center = self.position;
-        let color = self.color.components();
+        let bounds = Rect::from_points(geometry.vertices.iter().map(|(p, _)| *p));
 
-        let vert_count = points.len();
-        let idx_count = (points.len().saturating_sub(2)) * 3;
-
-        if let Some((verts, indices, base)) =
+        let mut world = VertexBuffers {
+            vertices: geometry
+                .vertices
+                .iter()
+                .map(|(p, uv)| {
+                    let world = rot * *p + center;
+                    let uv = match explicit_uvs {
+                        Some(_) => *uv,
+                        None => self.uv_mode.generate(*p, world, &bounds),
+                    };
+                    Vertex::new(world.into(), color, uv)
+                })
+                .collect(),
+            indices: geometry.indices,
+        };
+        if let Some(id) = self.hit_id {
             self.batch
-                .allocate(vert_count, idx_count, None, self.shader_id)
-        {
-            for (i, p) in points.iter().enumerate() {
-                let world = rot * *p + center;
-                verts[i] = Vertex::new(world.into(), color, [0.0, 0.0]);
-            }
+                .record_hit(id, triangles(&world.vertices, &world.indices));
+        }
+        if self.antialias {
+            feather(&mut world, FEATHER_PX / self.batch.zoom);
+        }
+        let repeat = self.uv_mode.repeats();
+        self.batch
+            .push_sampled(&world, self.tex_id, repeat, self.shader_id);
+    }
+}
+
+/// Triangulates a convex outline as a fan around its first point
+fn fan_polygon(points: &[Vec2], uvs: Option<&[[f32; 2]]>) -> VertexBuffers<(Vec2, [f32; 2]), u16> {
+    let mut geometry = VertexBuffers::with_capacity(points.len(), points.len() * 3);
+    for (i, p) in points.iter().enumerate() {
+        let uv = uvs.and_then(|uvs| uvs.get(i)).copied().unwrap_or_default();
+        geometry.vertices.push((*p, uv));
+    }
+    for i in 0..points.len().saturating_sub(2) as u16 {
+        geometry.indices.extend_from_slice(&[0, i + 1, i + 2]);
+    }
+    geometry
+}
+
+/// Tessellates a closed outline (plus optional holes) into triangles
+///
+/// `uvs` are interpolated across the tessellation, matching the outline
+/// points followed by the points of each hole.
+/// Returns `None` if the outline is degenerate or lyon fails to tessellate it
+fn fill_polygon(
+    points: &[Vec2],
+    holes: &[Vec<Vec2>],
+    rule: FillRule,
+    uvs: Option<&[[f32; 2]]>,
+) -> Option<VertexBuffers<(Vec2, [f32; 2]), u16>> {
+    if points.len() < 3 {
+        return None;
+    }
 
-            // Convex fan triangulation
-            for i in 0..points.len().saturating_sub(2) {
-                let offset = i * 3;
-                indices[offset] = base;
-                indices[offset + 1] = base + (i as u16 + 1);
-                indices[offset + 2] = base + (i as u16 + 2);
+    let mut builder = Path::builder_with_attributes(2);
+    let uvs = uvs.unwrap_or_default();
+    let mut first_uv = 0;
+    for contour in std::iter::once(points).chain(holes.iter().map(Vec::as_slice)) {
+        // skipped contours still own their uvs, the next contour starts after them
+        let start = first_uv;
+        first_uv += contour.len();
+        if contour.len() < 3 {
+            continue;
+        }
+        for (i, p) in contour.iter().enumerate() {
+            let uv = uvs.get(start + i).copied().unwrap_or_default();
+            if i == 0 {
+                builder.begin(point(p.x, p.y), &uv);
+            } else {
+                builder.line_to(point(p.x, p.y), &uv);
             }
         }
+        builder.end(true);
     }
+    let path = builder.build();
+
+    let mut geometry = VertexBuffers::new();
+    FillTessellator::new()
+        .tessellate_path(
+            &path,
+            &FillOptions::default().with_fill_rule(rule.into()),
+            &mut BuffersBuilder::new(&mut geometry, |mut vertex: FillVertex| {
+                let [x, y] = vertex.position().to_array();
+                let attributes = vertex.interpolated_attributes();
+                (vec2(x, y), [attributes[0], attributes[1]])
+            }),
+        )
+        .ok()?;
+
+    Some(geometry)
 }
 
 /// Builder for stroked paths (polylines)
@@ -277,6 +932,8 @@ pub struct PolylineBuilder<'a> {
     thickness: f32,
     color: Color,
     closed: bool,
+    antialias: bool,
+    hit_id: Option<u64>,
 }
 
 impl<'a> PolylineBuilder<'a> {
@@ -290,6 +947,8 @@ impl<'a> PolylineBuilder<'a> {
             thickness: 1.0,
             color: Color::WHITE,
             closed: false,
+            antialias: false,
+            hit_id: None,
         }
     }
     /// Sets the world-space position of the polyline
@@ -324,6 +983,16 @@ impl<'a> PolylineBuilder<'a> {
         self.closed = closed;
         self
     }
+    /// Records the drawn outline under `id` for hit testing
+    pub fn id(mut self, id: u64) -> Self {
+        self.hit_id = Some(id);
+        self
+    }
+    /// Feathers the outer edges over 1 screen pixel for smooth edges without MSAA
+    pub fn antialias(mut self, enabled: bool) -> Self {
+        self.antialias = enabled;
+        self
+    }
 }
 
 impl Drop for PolylineBuilder<'_> {
@@ -334,56 +1003,101 @@ impl Drop for PolylineBuilder<'_> {
         }
 
         let rot = Mat2::from_angle(self.rotation);
+        let half = Vec2::splat(self.thickness * 0.5);
+        let line = Rect::from_points(self.points.iter().copied());
+        let local = Rect::new(line.position - half, line.size + half * 2.0);
+        if !self
+            .batch
+            .is_visible(&transformed_bounds(&local, Vec2::ONE, rot, self.position))
+        {
+            return;
+        }
         let color = self.color.components();
         let segments = if self.closed { n } else { n - 1 };
-        let vert_count = segments * 4;
-        let idx_count = segments * 6;
+        let mut geometry = VertexBuffers::with_capacity(segments * 4, segments * 6);
 
-        if let Some((verts, indices, mut base)) =
-            self.batch
-                .allocate(vert_count, idx_count, None, self.shader_id)
-        {
-            let mut vi = 0;
-            let mut ii = 0;
-
-            for s in 0..segments {
-                let a = self.points[s];
-                let b = self.points[(s + 1) % n]; // wraps if closed
-
-                let dir = (b - a).normalize();
-                let nrm = vec2(-dir.y, dir.x) * (self.thickness * 0.5);
-
-                let p = [
-                    rot * (a + nrm) + self.position,
-                    rot * (a - nrm) + self.position,
-                    rot * (b - nrm) + self.position,
-                    rot * (b + nrm) + self.position,
-                ];
-
-                for &pos in &p {
-                    verts[vi] = Vertex::new(pos.into(), color, [0.0, 0.0]);
-                    vi += 1;
-                }
+        for s in 0..segments {
+            let a = self.points[s];
+            let b = self.points[(s + 1) % n]; // wraps if closed
+
+            let dir = (b - a).normalize();
+            let nrm = vec2(-dir.y, dir.x) * (self.thickness * 0.5);
+
+            let p = [
+                rot * (a + nrm) + self.position,
+                rot * (a - nrm) + self.position,
+                rot * (b - nrm) + self.position,
+                rot * (b + nrm) + self.position,
+            ];
 
-                indices[ii..ii + 6].copy_from_slice(&[
-                    base,
-                    base + 1,
-                    base + 2,
-                    base + 2,
-                    base + 3,
-                    base,
-                ]);
-                ii += 6;
-                base += 4;
+            let base = geometry.vertices.len() as u16;
+            for &pos in &p {
+                geometry
+                    .vertices
+                    .push(Vertex::new(pos.into(), color, [0.0, 0.0]));
             }
+            geometry.indices.extend_from_slice(&[
+                base,
+                base + 1,
+                base + 2,
+                base + 2,
+                base + 3,
+                base,
+            ]);
+        }
+
+        if let Some(id) = self.hit_id {
+            self.batch
+                .record_hit(id, triangles(&geometry.vertices, &geometry.indices));
         }
+        if self.antialias {
+            feather(&mut geometry, FEATHER_PX / self.batch.zoom);
+        }
+        self.batch.push(&geometry, None, self.shader_id);
     }
 }
 
+/// Shapes tessellated by [`ShapeBuilder`]
+///
+/// Angles are in radians, starting at +X & increasing clockwise on screen (Y points down).
+/// A sweep of a full turn (`TAU`) or more produces a closed outline
 pub enum Shape {
-    Path { steps: Vec<PathStep> },
-    Rect { size: Vec2 },
-    Circle { center: Vec2, radius: f32 },
+    Path {
+        steps: Vec<PathStep>,
+    },
+    Rect {
+        size: Vec2,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Ellipse {
+        center: Vec2,
+        radii: Vec2,
+    },
+    /// An open circular arc, mostly useful stroked (filling closes it with a chord)
+    Arc {
+        center: Vec2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// A pie slice: an arc closed through its center
+    Pie {
+        center: Vec2,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
+    /// A donut or partial donut between `inner_radius` & `radius`
+    Ring {
+        center: Vec2,
+        radius: f32,
+        inner_radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+    },
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -404,6 +1118,10 @@ pub struct ShapeBuilder<'a> {
     stroke_color: Option<Color>,
     fill_color: Option<Color>,
     shape: Option<Shape>,
+    tex_id: Option<usize>,
+    uv_mode: UvMode,
+    antialias: bool,
+    hit_id: Option<u64>,
 }
 
 impl<'a> ShapeBuilder<'a> {
@@ -418,6 +1136,10 @@ impl<'a> ShapeBuilder<'a> {
             stroke_color: None,
             fill_color: None,
             shape: None,
+            tex_id: None,
+            uv_mode: UvMode::Fit,
+            antialias: false,
+            hit_id: None,
         }
     }
 
@@ -456,117 +1178,719 @@ impl<'a> ShapeBuilder<'a> {
         self.shape = Some(shape);
         self
     }
+    /// Sets the texture ID for the shape's fill & stroke
+    pub fn texture(mut self, id: usize) -> Self {
+        self.tex_id = Some(id);
+        self
+    }
+    /// Sets how texture coordinates are generated  
+    /// Defaults to [`UvMode::Fit`], [`UvMode::Explicit`] is not supported for shapes
+    pub fn uv_mode(mut self, mode: UvMode) -> Self {
+        self.uv_mode = mode;
+        self
+    }
+    /// Feathers the outer edges over 1 screen pixel for smooth edges without MSAA
+    pub fn antialias(mut self, enabled: bool) -> Self {
+        self.antialias = enabled;
+        self
+    }
+    /// Records the drawn outline under `id` for hit testing
+    pub fn id(mut self, id: u64) -> Self {
+        self.hit_id = Some(id);
+        self
+    }
 }
 
 impl Drop for ShapeBuilder<'_> {
     fn drop(&mut self) {
-        let mut builder = Path::builder();
-
-        if let Some(shape) = &self.shape {
-            match shape {
-                Shape::Path { steps } => {
-                    for step in steps {
-                        match step {
-                            PathStep::Begin(v) => {
-                                builder.begin(point(v.x, v.y));
-                            }
-                            PathStep::LineTo(v) => {
-                                builder.line_to(point(v.x, v.y));
-                            }
-                            PathStep::QuadBezierTo(v1, v2) => {
-                                builder.quadratic_bezier_to(point(v1.x, v1.y), point(v2.x, v2.y));
-                            }
-                            PathStep::CubicBezierTo(v1, v2, v3) => {
-                                builder.cubic_bezier_to(
-                                    point(v1.x, v1.y),
-                                    point(v2.x, v2.y),
-                                    point(v3.x, v3.y),
-                                );
-                            }
-                        }
-                    }
-
-                    builder.end(true);
-                }
-                Shape::Rect { size } => {
-                    builder.add_rectangle(
-                        &Box2D::new(
-                            Point2D::new(self.position.x, self.position.y),
-                            Point2D::new(self.position.x + size.x, self.position.y + size.y),
-                        ),
-                        Winding::Positive,
-                    );
-                }
-                Shape::Circle { center, radius } => {
-                    builder.add_circle(Point::new(center.x, center.y), *radius, Winding::Positive);
-                }
-            }
+        let tolerance = path_tolerance(self.scale, self.batch.zoom);
+        let path = self
+            .shape
+            .as_ref()
+            .map(|shape| shape_path(shape, self.position, tolerance))
+            .unwrap_or_else(|| Path::builder().build());
+        let rot = Mat2::from_angle(self.rotation);
+        let path_box = fast_bounding_box(&path);
+        let half = Vec2::splat(self.stroke_color.map_or(0.0, |_| self.thickness * 0.5));
+        let local = Rect::new(
+            vec2(path_box.min.x, path_box.min.y) - half,
+            vec2(path_box.width(), path_box.height()) + half * 2.0,
+        );
+        if !self
+            .batch
+            .is_visible(&transformed_bounds(&local, self.scale, rot, self.position))
+        {
+            return;
         }
-
-        let path = builder.build();
-        let mut geometry: VertexBuffers<Vertex, u16> = VertexBuffers::new();
+        let mut fill = VertexBuffers::new();
+        let mut stroke = VertexBuffers::new();
 
         if let Some(fill_color) = self.fill_color {
-            let mut tessellator = FillTessellator::new();
-            {
-                tessellator
-                    .tessellate_path(
-                        &path,
-                        &FillOptions::default(),
-                        &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| {
-                            let [x, y] = vertex.position().to_array();
-                            Vertex {
-                                position: [x, y],
-                                color: fill_color.components(),
-                                tex_coords: [0.0, 0.0],
-                            }
-                        }),
-                    )
-                    .unwrap();
-            }
+            fill_path(
+                &path,
+                fill_color,
+                &FillOptions::tolerance(tolerance),
+                &mut fill,
+            )
+            .unwrap();
         }
 
         if let Some(stroke_color) = self.stroke_color {
-            let mut tessellator = StrokeTessellator::new();
-            {
-                tessellator
-                    .tessellate_path(
-                        &path,
-                        &StrokeOptions::default().with_line_width(self.thickness),
-                        &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| {
-                            let [x, y] = vertex.position().to_array();
-                            Vertex {
-                                position: [x, y],
-                                color: stroke_color.components(),
-                                tex_coords: [0.0, 0.0],
-                            }
-                        }),
-                    )
-                    .unwrap();
+            stroke_path(
+                &path,
+                stroke_color,
+                &StrokeOptions::tolerance(tolerance).with_line_width(self.thickness),
+                &mut stroke,
+            )
+            .unwrap();
+        }
+
+        let bounds = Rect::from_points(
+            fill.vertices
+                .iter()
+                .chain(&stroke.vertices)
+                .map(|v| v.position.into()),
+        );
+
+        for v in fill.vertices.iter_mut().chain(&mut stroke.vertices) {
+            let local: Vec2 = v.position.into();
+            let p = rot * (self.scale * local) + self.position;
+            v.position = p.to_array();
+            v.tex_coords = self.uv_mode.generate(local, p, &bounds);
+        }
+        if let Some(id) = self.hit_id {
+            let fill_tris = triangles(&fill.vertices, &fill.indices);
+            let stroke_tris = triangles(&stroke.vertices, &stroke.indices);
+            self.batch.record_hit(id, fill_tris.chain(stroke_tris));
+        }
+
+        // Fill & stroke are feathered separately so the fill fringe stays under the stroke
+        for mut geometry in [fill, stroke] {
+            if self.antialias {
+                feather(&mut geometry, FEATHER_PX / self.batch.zoom);
             }
+            let repeat = self.uv_mode.repeats();
+            self.batch
+                .push_sampled(&geometry, self.tex_id, repeat, self.shader_id);
         }
+    }
+}
 
-        let rot = Mat2::from_angle(self.rotation);
+/// World bounds of `local` bounds drawn with `scale`, then `rot`, at `position`
+pub(crate) fn transformed_bounds(local: &Rect, scale: Vec2, rot: Mat2, position: Vec2) -> Rect {
+    Rect::from_points(local.corners().map(|c| rot * (scale * c) + position))
+}
 
-        let vert_count = geometry.vertices.len();
-        let idx_count = geometry.indices.len();
+/// Tessellation tolerance in screen pixels
+pub(crate) const TOLERANCE_PX: f32 = 0.1;
 
-        if let Some((verts, indices, base)) =
-            self.batch.allocate(vert_count, idx_count, None, self.shader_id)
-        {
-            let mut vi = 0;
-            for mut vo in geometry.vertices {
-                let mut p: Vec2 = vo.position.into();
-                p = rot * (self.scale * p) + self.position;
-                vo.position = p.to_array();
-
-                verts[vi] = vo;
-                vi += 1;
+/// Converts [`TOLERANCE_PX`] into path units for a shape drawn with `scale` at camera `zoom`
+///
+/// Bigger on-screen shapes get a finer tolerance & therefore more segments
+fn path_tolerance(scale: Vec2, zoom: f32) -> f32 {
+    let pixels_per_unit = scale.abs().max_element() * zoom;
+    if pixels_per_unit > 0.0 {
+        TOLERANCE_PX / pixels_per_unit
+    } else {
+        TOLERANCE_PX
+    }
+}
+
+/// Width of the anti-aliasing fringe in screen pixels
+const FEATHER_PX: f32 = 1.0;
+
+/// Adds a fringe of `width` around the outer edges of world-space triangles, fading alpha to 0
+///
+/// Outer edges are those used by a single triangle, vertices are matched by position
+/// so tessellators emitting duplicates still share edges. Fringe vertices are pushed
+/// out along the mitered edge normals so corners stay closed
+fn feather(geometry: &mut VertexBuffers<Vertex, u16>, width: f32) {
+    // Canonical vertex per distinct position
+    let mut canonical = HashMap::new();
+    let canon: Vec<u16> = (0..geometry.vertices.len() as u16)
+        .map(|i| {
+            let [x, y] = geometry.vertices[i as usize].position;
+            *canonical.entry((x.to_bits(), y.to_bits())).or_insert(i)
+        })
+        .collect();
+
+    // Edge -> (use count, directed edge, opposite vertex) for non-degenerate triangles
+    let mut edges: HashMap<(u16, u16), (u32, u16, u16, u16)> = HashMap::new();
+    for tri in geometry.indices.chunks_exact(3) {
+        let p: [Vec2; 3] =
+            std::array::from_fn(|k| geometry.vertices[tri[k] as usize].position.into());
+        if (p[1] - p[0]).perp_dot(p[2] - p[0]).abs() <= f32::EPSILON {
+            continue;
+        }
+        for k in 0..3 {
+            let (a, b, opposite) = (tri[k], tri[(k + 1) % 3], tri[(k + 2) % 3]);
+            let (ca, cb) = (canon[a as usize], canon[b as usize]);
+            let key = (ca.min(cb), ca.max(cb));
+            edges.entry(key).or_insert((0, a, b, opposite)).0 += 1;
+        }
+    }
+
+    let position = |i: u16| Vec2::from(geometry.vertices[i as usize].position);
+    let mut outer_edges = Vec::new();
+    let mut normals: HashMap<u16, Vec<Vec2>> = HashMap::new();
+    for &(count, a, b, opposite) in edges.values() {
+        if count != 1 {
+            continue;
+        }
+        let mut n = (position(b) - position(a)).perp().normalize_or_zero();
+        if n.dot(position(opposite) - position(a)) > 0.0 {
+            n = -n;
+        }
+        normals.entry(canon[a as usize]).or_default().push(n);
+        normals.entry(canon[b as usize]).or_default().push(n);
+        outer_edges.push((a, b));
+    }
+
+    if geometry.vertices.len() + normals.len() > u16::MAX as usize {
+        return;
+    }
+
+    let mut fringe = HashMap::new();
+    for (&c, ns) in &normals {
+        let sum: Vec2 = ns.iter().sum();
+        let miter = sum.try_normalize().unwrap_or(ns[0]);
+        let min_dot = ns.iter().map(|n| miter.dot(*n)).fold(1.0, f32::min);
+        // Limits spikes at very sharp corners
+        let offset = miter * width / min_dot.max(0.25);
+
+        let mut v = geometry.vertices[c as usize];
+        v.position = (Vec2::from(v.position) + offset).to_array();
+        v.color[3] = 0.0;
+        fringe.insert(c, geometry.vertices.len() as u16);
+        geometry.vertices.push(v);
+    }
+
+    for (a, b) in outer_edges {
+        let (oa, ob) = (fringe[&canon[a as usize]], fringe[&canon[b as usize]]);
+        geometry.indices.extend_from_slice(&[a, b, ob, a, ob, oa]);
+    }
+}
+
+/// Fills `path` with a solid `color`, appending the triangles to `out`
+pub(crate) fn fill_path(
+    path: &Path,
+    color: Color,
+    options: &FillOptions,
+    out: &mut VertexBuffers<Vertex, u16>,
+) -> TessellationResult {
+    FillTessellator::new().tessellate_path(
+        path,
+        options,
+        &mut BuffersBuilder::new(out, |vertex: FillVertex| Vertex {
+            position: vertex.position().to_array(),
+            color: color.components(),
+            tex_coords: [0.0, 0.0],
+        }),
+    )
+}
+
+/// Strokes `path` with a solid `color`, appending the triangles to `out`
+pub(crate) fn stroke_path(
+    path: &Path,
+    color: Color,
+    options: &StrokeOptions,
+    out: &mut VertexBuffers<Vertex, u16>,
+) -> TessellationResult {
+    StrokeTessellator::new().tessellate_path(
+        path,
+        options,
+        &mut BuffersBuilder::new(out, |vertex: StrokeVertex| Vertex {
+            position: vertex.position().to_array(),
+            color: color.components(),
+            tex_coords: [0.0, 0.0],
+        }),
+    )
+}
+
+/// Builds the lyon path for a [`Shape`], flattening arcs within `tolerance`
+fn shape_path(shape: &Shape, position: Vec2, tolerance: f32) -> Path {
+    let mut builder = Path::builder();
+
+    match shape {
+        Shape::Path { steps } => {
+            for step in steps {
+                match step {
+                    PathStep::Begin(v) => {
+                        builder.begin(point(v.x, v.y));
+                    }
+                    PathStep::LineTo(v) => {
+                        builder.line_to(point(v.x, v.y));
+                    }
+                    PathStep::QuadBezierTo(v1, v2) => {
+                        builder.quadratic_bezier_to(point(v1.x, v1.y), point(v2.x, v2.y));
+                    }
+                    PathStep::CubicBezierTo(v1, v2, v3) => {
+                        builder.cubic_bezier_to(
+                            point(v1.x, v1.y),
+                            point(v2.x, v2.y),
+                            point(v3.x, v3.y),
+                        );
+                    }
+                }
             }
 
-            for i in 0..idx_count {
-                indices[i] = base + geometry.indices[i];
+            builder.end(true);
+        }
+        Shape::Rect { size } => {
+            builder.add_rectangle(
+                &Box2D::new(
+                    Point2D::new(position.x, position.y),
+                    Point2D::new(position.x + size.x, position.y + size.y),
+                ),
+                Winding::Positive,
+            );
+        }
+        Shape::Circle { center, radius } => {
+            builder.add_circle(Point::new(center.x, center.y), *radius, Winding::Positive);
+        }
+        Shape::Ellipse { center, radii } => {
+            builder.add_ellipse(
+                point(center.x, center.y),
+                vector(radii.x, radii.y),
+                Angle::zero(),
+                Winding::Positive,
+            );
+        }
+        Shape::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let sweep = end_angle - start_angle;
+            let arc = arc(*center, *radius, *start_angle, sweep);
+            builder.begin(arc.from());
+            add_arc(&mut builder, &arc, tolerance);
+            builder.end(sweep.abs() >= TAU);
+        }
+        Shape::Pie {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+        } => {
+            let sweep = end_angle - start_angle;
+            if sweep.abs() >= TAU {
+                builder.add_circle(point(center.x, center.y), *radius, Winding::Positive);
+            } else {
+                let arc = arc(*center, *radius, *start_angle, sweep);
+                builder.begin(point(center.x, center.y));
+                builder.line_to(arc.from());
+                add_arc(&mut builder, &arc, tolerance);
+                builder.end(true);
+            }
+        }
+        Shape::Ring {
+            center,
+            radius,
+            inner_radius,
+            start_angle,
+            end_angle,
+        } => {
+            let sweep = end_angle - start_angle;
+            let c = point(center.x, center.y);
+            if sweep.abs() >= TAU {
+                builder.add_circle(c, *radius, Winding::Positive);
+                builder.add_circle(c, *inner_radius, Winding::Negative);
+            } else {
+                let outer = arc(*center, *radius, *start_angle, sweep);
+                let inner = arc(*center, *inner_radius, *end_angle, -sweep);
+                builder.begin(outer.from());
+                add_arc(&mut builder, &outer, tolerance);
+                builder.line_to(inner.from());
+                add_arc(&mut builder, &inner, tolerance);
+                builder.end(true);
             }
         }
     }
+
+    builder.build()
+}
+
+/// Creates a circular arc, clamping the sweep to a full turn
+fn arc(center: Vec2, radius: f32, start_angle: f32, sweep: f32) -> lyon::geom::Arc<f32> {
+    lyon::geom::Arc {
+        center: point(center.x, center.y),
+        radii: vector(radius, radius),
+        start_angle: Angle::radians(start_angle),
+        sweep_angle: Angle::radians(sweep.clamp(-TAU, TAU)),
+        x_rotation: Angle::zero(),
+    }
+}
+
+/// Appends an arc to an already started sub-path, flattened to line segments within `tolerance`
+fn add_arc(builder: &mut lyon::path::path::Builder, arc: &lyon::geom::Arc<f32>, tolerance: f32) {
+    arc.for_each_flattened(tolerance, &mut |segment| {
+        builder.line_to(segment.to);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangles_area(geometry: &VertexBuffers<Vec2, u16>) -> f32 {
+        geometry
+            .indices
+            .chunks(3)
+            .map(|t| {
+                let [a, b, c] = [0, 1, 2].map(|i| geometry.vertices[t[i] as usize]);
+                (b - a).perp_dot(c - a).abs() * 0.5
+            })
+            .sum()
+    }
+
+    fn polygon_area(geometry: &VertexBuffers<(Vec2, [f32; 2]), u16>) -> f32 {
+        triangles_area(&VertexBuffers {
+            vertices: geometry.vertices.iter().map(|(p, _)| *p).collect(),
+            indices: geometry.indices.clone(),
+        })
+    }
+
+    #[test]
+    fn concave_polygon_stays_inside_outline() {
+        // an L-shaped room, a convex fan would cover the missing corner
+        let l_shape = [
+            vec2(0.0, 0.0),
+            vec2(2.0, 0.0),
+            vec2(2.0, 1.0),
+            vec2(1.0, 1.0),
+            vec2(1.0, 2.0),
+            vec2(0.0, 2.0),
+        ];
+        let geometry = fill_polygon(&l_shape, &[], FillRule::NonZero, None).unwrap();
+        assert!((polygon_area(&geometry) - 3.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn holes_are_cut_out() {
+        // outer square wound one way, hole wound the other
+        let outer = [
+            vec2(0.0, 0.0),
+            vec2(4.0, 0.0),
+            vec2(4.0, 4.0),
+            vec2(0.0, 4.0),
+        ];
+        let hole = vec![
+            vec2(1.0, 1.0),
+            vec2(1.0, 3.0),
+            vec2(3.0, 3.0),
+            vec2(3.0, 1.0),
+        ];
+        let geometry = fill_polygon(&outer, &[hole], FillRule::NonZero, None).unwrap();
+        assert!((polygon_area(&geometry) - 12.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn fill_rule_changes_self_intersecting_fill() {
+        // a pentagram: even-odd leaves the center pentagon empty, non-zero fills it
+        let star: Vec<Vec2> = (0..5)
+            .map(|i| {
+                let t = (i * 2) as f32 / 5.0 * std::f32::consts::TAU;
+                vec2(t.cos(), t.sin())
+            })
+            .collect();
+        let non_zero = fill_polygon(&star, &[], FillRule::NonZero, None).unwrap();
+        let even_odd = fill_polygon(&star, &[], FillRule::EvenOdd, None).unwrap();
+        assert!(polygon_area(&non_zero) > polygon_area(&even_odd) + 0.1);
+    }
+
+    #[test]
+    fn degenerate_polygon_is_skipped() {
+        assert!(
+            fill_polygon(
+                &[vec2(0.0, 0.0), vec2(1.0, 0.0)],
+                &[],
+                FillRule::NonZero,
+                None
+            )
+            .is_none()
+        );
+    }
+
+    fn filled_area(shape: &Shape) -> f32 {
+        let mut geometry: VertexBuffers<Vec2, u16> = VertexBuffers::new();
+        FillTessellator::new()
+            .tessellate_path(
+                &shape_path(shape, Vec2::ZERO, 0.001),
+                &FillOptions::tolerance(0.001),
+                &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| {
+                    let [x, y] = vertex.position().to_array();
+                    vec2(x, y)
+                }),
+            )
+            .unwrap();
+        triangles_area(&geometry)
+    }
+
+    #[test]
+    fn pie_and_ring_areas() {
+        // a quarter pie & a half ring should cover the matching fraction of their discs
+        let quarter = filled_area(&Shape::Pie {
+            center: Vec2::ZERO,
+            radius: 10.0,
+            start_angle: 0.0,
+            end_angle: TAU / 4.0,
+        });
+        assert!((quarter - 100.0 * TAU / 8.0).abs() < 0.1);
+
+        let half_ring = filled_area(&Shape::Ring {
+            center: Vec2::ZERO,
+            radius: 10.0,
+            inner_radius: 5.0,
+            start_angle: 0.0,
+            end_angle: TAU / 2.0,
+        });
+        assert!((half_ring - 75.0 * TAU / 4.0).abs() < 0.1);
+
+        let full_ring = filled_area(&Shape::Ring {
+            center: Vec2::ZERO,
+            radius: 10.0,
+            inner_radius: 5.0,
+            start_angle: 0.0,
+            end_angle: TAU,
+        });
+        assert!((full_ring - 75.0 * TAU / 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn tolerance_follows_on_screen_size() {
+        // zooming in or scaling up should tighten the tolerance in path units
+        let base = path_tolerance(Vec2::ONE, 1.0);
+        assert!(path_tolerance(Vec2::ONE, 4.0) < base);
+        assert!(path_tolerance(Vec2::splat(4.0), 1.0) < base);
+        assert!((path_tolerance(Vec2::splat(2.0), 2.0) - base / 4.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn explicit_uvs_are_interpolated() {
+        // every tessellated vertex of a concave outline keeps the uv of the point it sits on
+        let points = [
+            vec2(0.0, 0.0),
+            vec2(2.0, 0.0),
+            vec2(2.0, 1.0),
+            vec2(1.0, 1.0),
+            vec2(1.0, 2.0),
+            vec2(0.0, 2.0),
+        ];
+        let uvs: Vec<[f32; 2]> = points.iter().map(|p| (*p / 2.0).into()).collect();
+        let geometry = fill_polygon(&points, &[], FillRule::NonZero, Some(&uvs)).unwrap();
+        for (p, uv) in &geometry.vertices {
+            assert!((*p / 2.0 - Vec2::from(*uv)).length() < 0.001);
+        }
+    }
+
+    #[test]
+    fn degenerate_holes_keep_uvs_aligned() {
+        // a 2 point hole is skipped, the square hole after it still gets its own uvs
+        let outer = [
+            vec2(0.0, 0.0),
+            vec2(4.0, 0.0),
+            vec2(4.0, 4.0),
+            vec2(0.0, 4.0),
+        ];
+        let holes = vec![
+            vec![vec2(0.5, 0.5), vec2(0.6, 0.6)],
+            vec![
+                vec2(1.0, 1.0),
+                vec2(1.0, 3.0),
+                vec2(3.0, 3.0),
+                vec2(3.0, 1.0),
+            ],
+        ];
+        let uvs: Vec<[f32; 2]> = std::iter::once(&outer[..])
+            .chain(holes.iter().map(Vec::as_slice))
+            .flatten()
+            .map(|p| (*p / 4.0).into())
+            .collect();
+        let geometry = fill_polygon(&outer, &holes, FillRule::NonZero, Some(&uvs)).unwrap();
+        for (p, uv) in &geometry.vertices {
+            assert!((*p / 4.0 - Vec2::from(*uv)).length() < 0.001);
+        }
+    }
+
+    #[test]
+    fn generated_uvs() {
+        // fit maps the local bounds to 0..1, tile repeats once per `size` world units
+        let bounds = Rect::new(vec2(-1.0, -1.0), vec2(2.0, 4.0));
+        let fit = UvMode::Fit.generate(vec2(1.0, 3.0), Vec2::ZERO, &bounds);
+        assert_eq!(fit, [1.0, 1.0]);
+
+        let tile = UvMode::Tile {
+            size: vec2(10.0, 20.0),
+            offset: vec2(5.0, 0.0),
+        };
+        assert_eq!(
+            tile.generate(Vec2::ZERO, vec2(25.0, 40.0), &bounds),
+            [2.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn fan_triangulates_convex_outline() {
+        let square = [
+            vec2(0.0, 0.0),
+            vec2(1.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.0),
+        ];
+        let geometry = fan_polygon(&square, None);
+        assert_eq!(geometry.indices, [0, 1, 2, 0, 2, 3]);
+        assert!((polygon_area(&geometry) - 1.0).abs() < f32::EPSILON);
+    }
+
+    fn square_vertices(positions: &[[f32; 2]]) -> Vec<Vertex> {
+        positions
+            .iter()
+            .map(|&p| Vertex::new(p, [1.0; 4], [0.0; 2]))
+            .collect()
+    }
+
+    #[test]
+    fn feather_fringes_outer_edges() {
+        let mut geometry = VertexBuffers {
+            vertices: square_vertices(&[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]]),
+            indices: vec![0, 1, 2, 0, 2, 3],
+        };
+        feather(&mut geometry, 1.0);
+
+        // 4 mitered corners, 2 triangles per outer edge, diagonal left alone
+        assert_eq!(geometry.vertices.len(), 8);
+        assert_eq!(geometry.indices.len(), 6 + 4 * 6);
+        let corner = geometry.vertices[4..]
+            .iter()
+            .find(|v| v.position[0] < 0.0 && v.position[1] < 0.0)
+            .unwrap();
+        assert!((Vec2::from(corner.position) - vec2(-1.0, -1.0)).length() < 1e-5);
+        assert_eq!(corner.color[3], 0.0);
+    }
+
+    #[test]
+    fn feather_matches_duplicate_vertices() {
+        // each triangle owns its vertices, the shared diagonal is still interior
+        let mut geometry = VertexBuffers {
+            vertices: square_vertices(&[
+                [0.0, 0.0],
+                [10.0, 0.0],
+                [10.0, 10.0],
+                [0.0, 0.0],
+                [10.0, 10.0],
+                [0.0, 10.0],
+            ]),
+            indices: vec![0, 1, 2, 3, 4, 5],
+        };
+        feather(&mut geometry, 1.0);
+
+        assert_eq!(geometry.vertices.len(), 6 + 4);
+        assert_eq!(geometry.indices.len(), 6 + 4 * 6);
+    }
+
+    #[test]
+    fn source_rect_to_uvs() {
+        // 3rd 32x32 frame of a 128x64 sheet
+        let source = Rect::new(vec2(64.0, 0.0), vec2(32.0, 32.0));
+        let uvs = source_uvs(&source, vec2(128.0, 64.0));
+        assert_eq!(uvs, [[0.5, 0.0], [0.75, 0.0], [0.75, 0.5], [0.5, 0.5]]);
+    }
+
+    #[test]
+    fn flipped_uvs() {
+        let uvs = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        assert_eq!(
+            flip_uvs(uvs, true, false),
+            [[1.0, 0.0], [0.0, 0.0], [0.0, 1.0], [1.0, 1.0]]
+        );
+        assert_eq!(
+            flip_uvs(uvs, false, true),
+            [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]
+        );
+        assert_eq!(
+            flip_uvs(uvs, true, true),
+            [[1.0, 1.0], [0.0, 1.0], [0.0, 0.0], [1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn anchor_pivots() {
+        assert_eq!(Anchor::TopLeft.pivot(), vec2(0.0, 0.0));
+        assert_eq!(Anchor::Center.pivot(), vec2(0.5, 0.5));
+        assert_eq!(Anchor::BottomCenter.pivot(), vec2(0.5, 1.0));
+        assert_eq!(Anchor::CenterRight.pivot(), vec2(1.0, 0.5));
+    }
+
+    #[test]
+    fn nine_slice_stretches() {
+        // 32x32 texture with 8px borders into a 100x50 rect
+        let dest = Rect::new(vec2(10.0, 0.0), vec2(100.0, 50.0));
+        let quads = nine_slice_quads(&dest, vec2(32.0, 32.0), [8.0; 4], 1.0, false, false);
+        assert_eq!(quads.len(), 9);
+
+        // corners keep their size, center stretches
+        let (corner, corner_uv) = &quads[0];
+        assert_eq!(*corner, Rect::new(vec2(10.0, 0.0), vec2(8.0, 8.0)));
+        assert_eq!(*corner_uv, Rect::new(vec2(0.0, 0.0), vec2(0.25, 0.25)));
+        let (center, center_uv) = &quads[4];
+        assert_eq!(*center, Rect::new(vec2(18.0, 8.0), vec2(84.0, 34.0)));
+        assert_eq!(*center_uv, Rect::new(vec2(0.25, 0.25), vec2(0.5, 0.5)));
+    }
+
+    #[test]
+    fn nine_slice_tiles_edges() {
+        // 16px center tile across an 84 wide edge: 5 full tiles & a 4px remainder
+        let dest = Rect::new(Vec2::ZERO, vec2(100.0, 32.0));
+        let quads = nine_slice_quads(&dest, vec2(32.0, 32.0), [8.0; 4], 1.0, true, false);
+        let top: Vec<_> = quads
+            .iter()
+            .filter(|(r, _)| r.position.y == 0.0 && r.position.x >= 8.0 && r.position.x < 92.0)
+            .collect();
+        assert_eq!(top.len(), 6);
+        let (last, last_uv) = top[5];
+        assert_eq!(last.size, vec2(4.0, 8.0));
+        assert_eq!(last_uv.size, vec2(4.0 / 32.0, 0.25));
+    }
+
+    #[test]
+    fn nine_slice_shrinks_borders_to_fit() {
+        let dest = Rect::new(Vec2::ZERO, vec2(8.0, 8.0));
+        let quads = nine_slice_quads(&dest, vec2(32.0, 32.0), [8.0; 4], 1.0, false, false);
+        // center collapses, corners shrink to 4x4
+        assert_eq!(quads.len(), 4);
+        assert!(quads.iter().all(|(r, _)| r.size == vec2(4.0, 4.0)));
+    }
+
+    #[test]
+    fn offscreen_draws_are_culled() {
+        let mut batch = PrimitiveBatch {
+            visible: Some(Rect::new(Vec2::ZERO, vec2(100.0, 100.0))),
+            ..Default::default()
+        };
+        PolygonBuilder::new(&mut batch, None).at(vec2(50.0, 50.0));
+        PolygonBuilder::new(&mut batch, None).at(vec2(500.0, 50.0));
+        // only its rotated end reaches into view
+        PolylineBuilder::new(&mut batch, None)
+            .at(vec2(-20.0, 50.0))
+            .points(&[Vec2::ZERO, vec2(0.0, 60.0)])
+            .rotate(-std::f32::consts::FRAC_PI_2);
+
+        let stats = batch.cull_stats;
+        assert_eq!((stats.submitted, stats.culled), (3, 1));
+        assert!(!batch.take().is_empty());
+    }
+
+    #[test]
+    fn param_blocks_split_batches() {
+        let mut batch = PrimitiveBatch::default();
+        for params in [Some(256), Some(512), Some(256)] {
+            batch.params = params;
+            PolygonBuilder::new(&mut batch, Some(0));
+        }
+        let geometry = batch.take();
+        let blocks: Vec<_> = geometry.iter().map(|(p, _)| p.params).collect();
+        assert_eq!(blocks, [Some(256), Some(512)]);
+    }
 }
@@ -0,0 +1,123 @@
+use egor_app::input::CursorMapping;
+use egor_render::Viewport;
+use glam::{Vec2, vec2};
+
+use crate::math::Rect;
+
+/// How a virtual resolution set with [`crate::app::App::virtual_resolution`] is fitted into the window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Fills the window, distorting the aspect ratio if it differs
+    Stretch,
+    /// Largest uniform scale that fits, bars fill the rest of the window
+    #[default]
+    Fit,
+    /// Scales uniformly to fit, then grows the canvas along one axis so there are no bars  
+    /// [`crate::graphics::Graphics::screen_size`] is at least the virtual resolution
+    Expand,
+    /// Largest whole-number scale that fits, sampled without filtering for pixel-perfect art  
+    /// Falls back to [`Self::Fit`] when the window is smaller than the virtual resolution
+    IntegerScale,
+}
+
+/// Size of the canvas drawn into & where it is shown in the window, in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Letterbox {
+    pub(crate) canvas: (u32, u32),
+    pub(crate) dest: Rect,
+}
+
+impl ScaleMode {
+    /// Lays out a `virtual_size` canvas in a `window` sized backbuffer
+    pub(crate) fn letterbox(self, virtual_size: (u32, u32), window: (u32, u32)) -> Letterbox {
+        let size = vec2(virtual_size.0 as f32, virtual_size.1 as f32).max(Vec2::ONE);
+        let win = vec2(window.0 as f32, window.1 as f32).max(Vec2::ONE);
+        let fit = (win / size).min_element();
+
+        let (canvas, dest_size) = match self {
+            Self::Stretch => (size, win),
+            Self::Fit => (size, (size * fit).round()),
+            Self::Expand => ((win / fit).round(), win),
+            Self::IntegerScale => {
+                let scale = if fit >= 1.0 { fit.floor() } else { fit };
+                (size, (size * scale).round())
+            }
+        };
+        let dest_size = dest_size.clamp(Vec2::ONE, win);
+        Letterbox {
+            canvas: (canvas.x as u32, canvas.y as u32),
+            dest: Rect::new(((win - dest_size) / 2.0).floor(), dest_size),
+        }
+    }
+}
+
+impl Letterbox {
+    /// Maps window pixels to canvas pixels
+    pub(crate) fn cursor_mapping(&self) -> CursorMapping {
+        let scale = vec2(self.canvas.0 as f32, self.canvas.1 as f32) / self.dest.size;
+        CursorMapping {
+            origin: self.dest.position.into(),
+            scale: scale.into(),
+        }
+    }
+
+    /// Window area the canvas is drawn into
+    pub(crate) fn viewport(&self) -> Viewport {
+        let Rect { position, size } = self.dest;
+        Viewport {
+            x: position.x as u32,
+            y: position.y as u32,
+            width: size.x as u32,
+            height: size.y as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIRTUAL: (u32, u32) = (320, 180);
+
+    #[test]
+    fn stretch_fills_window() {
+        let l = ScaleMode::Stretch.letterbox(VIRTUAL, (1000, 1000));
+        assert_eq!(l.canvas, VIRTUAL);
+        assert_eq!(l.dest, Rect::new(Vec2::ZERO, vec2(1000.0, 1000.0)));
+    }
+
+    #[test]
+    fn fit_adds_bars() {
+        // 16:9 in a 4:3 window letterboxes top & bottom
+        let l = ScaleMode::Fit.letterbox(VIRTUAL, (1024, 768));
+        assert_eq!(l.canvas, VIRTUAL);
+        assert_eq!(l.dest, Rect::new(vec2(0.0, 96.0), vec2(1024.0, 576.0)));
+        // & pillarboxes in a tall one
+        let l = ScaleMode::Fit.letterbox(VIRTUAL, (3200, 900));
+        assert_eq!(l.dest, Rect::new(vec2(800.0, 0.0), vec2(1600.0, 900.0)));
+    }
+
+    #[test]
+    fn expand_grows_canvas() {
+        let l = ScaleMode::Expand.letterbox(VIRTUAL, (1024, 768));
+        assert_eq!(l.canvas, (320, 240));
+        assert_eq!(l.dest, Rect::new(Vec2::ZERO, vec2(1024.0, 768.0)));
+    }
+
+    #[test]
+    fn integer_scale_is_whole() {
+        let l = ScaleMode::IntegerScale.letterbox(VIRTUAL, (1000, 700));
+        assert_eq!(l.dest, Rect::new(vec2(20.0, 80.0), vec2(960.0, 540.0)));
+        // smaller windows shrink like fit instead of overflowing
+        let l = ScaleMode::IntegerScale.letterbox(VIRTUAL, (160, 180));
+        assert_eq!(l.dest, Rect::new(vec2(0.0, 45.0), vec2(160.0, 90.0)));
+    }
+
+    #[test]
+    fn cursor_maps_into_canvas() {
+        let l = ScaleMode::Fit.letterbox(VIRTUAL, (1024, 768));
+        let mapping = l.cursor_mapping();
+        assert_eq!(mapping.position((0.0, 96.0)), (0.0, 0.0));
+        assert_eq!(mapping.position((1024.0, 672.0)), (320.0, 180.0));
+    }
+}
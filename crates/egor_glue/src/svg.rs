@@ -0,0 +1,243 @@
+use std::fmt;
+
+use egor_render::vertex::Vertex;
+use glam::{Vec2, vec2};
+use lyon::math::point;
+use lyon::path::Path;
+use lyon::tessellation::{FillOptions, LineCap, LineJoin, StrokeOptions, VertexBuffers};
+use usvg::tiny_skia_path::PathSegment;
+
+use crate::{
+    color::Color,
+    mesh::Mesh,
+    primitives::{FillRule, TOLERANCE_PX, fill_path, stroke_path},
+};
+
+/// Error returned when an SVG document can't be parsed
+#[derive(Debug)]
+pub struct SvgError(usvg::Error);
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to parse SVG: {}", self.0)
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+/// An SVG document tessellated into a reusable [`Mesh`]
+///
+/// Supports paths, basic shapes, transforms, solid fills & strokes, groups & opacity.
+/// Gradients, patterns, images & text are skipped.
+/// Draw it with [`crate::graphics::Graphics::svg`]
+#[derive(Clone, Debug)]
+pub struct SvgDocument {
+    mesh: Mesh,
+    size: Vec2,
+}
+
+impl SvgDocument {
+    /// Parse & tessellate an SVG document from bytes (`.svg` or `.svgz`)
+    ///
+    /// Curves are flattened for drawing at 1:1 scale, see [`Self::parse_with_tolerance`]
+    pub fn parse(data: &[u8]) -> Result<Self, SvgError> {
+        Self::parse_with_tolerance(data, TOLERANCE_PX)
+    }
+
+    /// Parse with an explicit curve flattening `tolerance` in SVG user units
+    /// Use a smaller tolerance for documents drawn scaled up
+    pub fn parse_with_tolerance(data: &[u8], tolerance: f32) -> Result<Self, SvgError> {
+        let tree = usvg::Tree::from_data(data, &usvg::Options::default()).map_err(SvgError)?;
+
+        let mut mesh = Mesh::new();
+        tessellate_group(tree.root(), 1.0, tolerance, &mut mesh);
+
+        let size = tree.size();
+        Ok(Self {
+            mesh,
+            size: vec2(size.width(), size.height()),
+        })
+    }
+
+    /// Document size in SVG user units (from `width`/`height` or the `viewBox`)
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+    /// The tessellated geometry, with the document's top-left at the origin
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+}
+
+/// Group opacity is multiplied down into each path, so overlapping children don't composite as one layer
+fn tessellate_group(group: &usvg::Group, opacity: f32, tolerance: f32, mesh: &mut Mesh) {
+    let opacity = opacity * group.opacity().get();
+
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(group) => tessellate_group(group, opacity, tolerance, mesh),
+            usvg::Node::Path(path) if path.is_visible() => {
+                tessellate_path(path, opacity, tolerance, mesh)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn tessellate_path(svg_path: &usvg::Path, opacity: f32, tolerance: f32, mesh: &mut Mesh) {
+    let transform = svg_path.abs_transform();
+    let Some(data) = svg_path.data().clone().transform(transform) else {
+        return;
+    };
+    let path = lyon_path(&data);
+
+    let fill = svg_path.fill().and_then(|fill| {
+        let color = paint_color(fill.paint(), fill.opacity().get() * opacity)?;
+        let rule = match fill.rule() {
+            usvg::FillRule::NonZero => FillRule::NonZero,
+            usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+        };
+        let options = FillOptions::tolerance(tolerance).with_fill_rule(rule.into());
+        Some((color, options))
+    });
+
+    let stroke = svg_path.stroke().and_then(|stroke| {
+        let color = paint_color(stroke.paint(), stroke.opacity().get() * opacity)?;
+        // The path is already transformed, so scale the width to match
+        let (sx, sy) = transform.get_scale();
+        let options = StrokeOptions::tolerance(tolerance)
+            .with_line_width(stroke.width().get() * (sx * sy).abs().sqrt())
+            .with_miter_limit(stroke.miterlimit().get().max(1.0))
+            .with_line_join(match stroke.linejoin() {
+                usvg::LineJoin::Miter => LineJoin::Miter,
+                usvg::LineJoin::MiterClip => LineJoin::MiterClip,
+                usvg::LineJoin::Round => LineJoin::Round,
+                usvg::LineJoin::Bevel => LineJoin::Bevel,
+            })
+            .with_line_cap(match stroke.linecap() {
+                usvg::LineCap::Butt => LineCap::Butt,
+                usvg::LineCap::Round => LineCap::Round,
+                usvg::LineCap::Square => LineCap::Square,
+            });
+        Some((color, options))
+    });
+
+    let mut fill_geometry = VertexBuffers::new();
+    let mut stroke_geometry = VertexBuffers::new();
+    if let Some((color, options)) = fill {
+        let _ = fill_path(&path, color, &options, &mut fill_geometry);
+    }
+    if let Some((color, options)) = stroke {
+        let _ = stroke_path(&path, color, &options, &mut stroke_geometry);
+    }
+
+    let mut push =
+        |geometry: VertexBuffers<Vertex, u16>| mesh.push(&geometry.vertices, &geometry.indices);
+    match svg_path.paint_order() {
+        usvg::PaintOrder::FillAndStroke => {
+            push(fill_geometry);
+            push(stroke_geometry);
+        }
+        usvg::PaintOrder::StrokeAndFill => {
+            push(stroke_geometry);
+            push(fill_geometry);
+        }
+    }
+}
+
+fn paint_color(paint: &usvg::Paint, opacity: f32) -> Option<Color> {
+    let usvg::Paint::Color(c) = paint else {
+        return None;
+    };
+    Some(Color::new([
+        c.red as f32 / 255.0,
+        c.green as f32 / 255.0,
+        c.blue as f32 / 255.0,
+        opacity,
+    ]))
+}
+
+fn lyon_path(data: &usvg::tiny_skia_path::Path) -> Path {
+    let mut builder = Path::builder();
+    let mut open = false;
+
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                if open {
+                    builder.end(false);
+                }
+                builder.begin(point(p.x, p.y));
+                open = true;
+            }
+            PathSegment::LineTo(p) => {
+                builder.line_to(point(p.x, p.y));
+            }
+            PathSegment::QuadTo(c, p) => {
+                builder.quadratic_bezier_to(point(c.x, c.y), point(p.x, p.y));
+            }
+            PathSegment::CubicTo(c1, c2, p) => {
+                builder.cubic_bezier_to(point(c1.x, c1.y), point(c2.x, c2.y), point(p.x, p.y));
+            }
+            PathSegment::Close => {
+                if open {
+                    builder.end(true);
+                    open = false;
+                }
+            }
+        }
+    }
+    if open {
+        builder.end(false);
+    }
+
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> SvgDocument {
+        let svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50">{body}</svg>"#
+        );
+        SvgDocument::parse(svg.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn rect_with_transform() {
+        let doc = parse(
+            r#"<rect x="0" y="0" width="10" height="20" fill="red" transform="translate(5 5)"/>"#,
+        );
+
+        assert_eq!(doc.size(), vec2(100.0, 50.0));
+        let bounds = doc.mesh().bounds();
+        assert_eq!(bounds.position, vec2(5.0, 5.0));
+        assert_eq!(bounds.size, vec2(10.0, 20.0));
+    }
+
+    #[test]
+    fn group_opacity_multiplies_fill_opacity() {
+        let doc = parse(
+            r##"<g opacity="0.5"><circle cx="10" cy="10" r="5" fill="#ff0000" fill-opacity="0.5"/></g>"##,
+        );
+
+        let vertex = doc.mesh().vertices().next().unwrap();
+        assert_eq!(vertex.color, [1.0, 0.0, 0.0, 0.25]);
+    }
+
+    #[test]
+    fn invisible_and_unpainted_paths_are_skipped() {
+        let doc = parse(
+            r#"<rect width="10" height="10" fill="none"/><rect width="10" height="10" visibility="hidden"/>"#,
+        );
+
+        assert!(doc.mesh().is_empty());
+    }
+
+    #[test]
+    fn invalid_data_errors() {
+        assert!(SvgDocument::parse(b"not svg").is_err());
+    }
+}
@@ -0,0 +1,533 @@
+use std::fmt;
+
+use glam::{Vec2, vec2};
+
+use crate::{
+    color::Color,
+    tilemap::{Tile, TileLayer, Tileset},
+};
+
+/// Error returned when a Tiled map can't be loaded
+#[derive(Debug)]
+pub enum TiledError {
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    #[cfg(feature = "tmx")]
+    Xml(roxmltree::Error),
+    /// A required attribute is missing or malformed
+    Invalid(String),
+    /// Valid Tiled data that isn't supported, e.g. infinite maps or compressed layers
+    Unsupported(String),
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json(e) => write!(f, "invalid Tiled JSON: {e}"),
+            #[cfg(feature = "tmx")]
+            Self::Xml(e) => write!(f, "invalid TMX: {e}"),
+            Self::Invalid(msg) => write!(f, "invalid Tiled map: {msg}"),
+            Self::Unsupported(msg) => write!(f, "unsupported Tiled map: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {}
+
+/// A tileset referenced by a [`TiledMap`], load its image & call [`Self::tileset`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    pub name: String,
+    /// Image path as written in the map, relative to the map file
+    pub image: String,
+    pub image_size: Vec2,
+    pub tile_size: Vec2,
+    pub margin: f32,
+    pub spacing: f32,
+}
+
+impl TiledTileset {
+    /// Image file name without its directory, handy for matching embedded assets
+    pub fn image_name(&self) -> &str {
+        self.image.rsplit(['/', '\\']).next().unwrap_or(&self.image)
+    }
+    /// A [`Tileset`] for this tileset's image loaded as `texture_id`
+    pub fn tileset(&self, texture_id: usize) -> Tileset {
+        Tileset::new(texture_id, self.image_size, self.tile_size)
+            .with_margin(self.margin)
+            .with_spacing(self.spacing)
+            .with_first_id(self.first_gid)
+    }
+}
+
+/// A tile layer of a [`TiledMap`], group layers are flattened into their children
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Global tile ids row by row, flip flags in the high bits
+    pub data: Vec<u32>,
+    pub visible: bool,
+    pub opacity: f32,
+    /// Pixel offset, including the offsets of parent groups
+    pub offset: Vec2,
+}
+
+/// An orthogonal, finite map made in the Tiled editor
+///
+/// Only tile layers are kept, object & image layers are skipped. Tilesets
+/// must be embedded in the map & be based on a single image
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: Vec2,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    /// Build a [`TileLayer`] from one of the map's layers
+    ///
+    /// Tiles whose tileset isn't in `tilesets` are skipped when drawing
+    pub fn tile_layer(&self, layer: &TiledLayer, tilesets: &[Tileset]) -> TileLayer {
+        let mut tile_layer = TileLayer::new(layer.width, layer.height, self.tile_size);
+        for tileset in tilesets {
+            tile_layer.add_tileset(tileset.clone());
+        }
+        tile_layer.set_tiles(layer.data.iter().map(|&gid| Tile::from_gid(gid)));
+        tile_layer.set_position(layer.offset);
+        tile_layer.set_visible(layer.visible);
+        tile_layer.set_color(Color::new([1.0, 1.0, 1.0, layer.opacity]));
+        tile_layer
+    }
+
+    /// Builds a [`TileLayer`] for every layer, in draw order
+    pub fn tile_layers(&self, tilesets: &[Tileset]) -> Vec<TileLayer> {
+        self.layers
+            .iter()
+            .map(|layer| self.tile_layer(layer, tilesets))
+            .collect()
+    }
+}
+
+/// Inherited state while flattening group layers
+#[derive(Clone, Copy)]
+struct Parent {
+    visible: bool,
+    opacity: f32,
+    offset: Vec2,
+}
+
+const ROOT: Parent = Parent {
+    visible: true,
+    opacity: 1.0,
+    offset: Vec2::ZERO,
+};
+
+fn check_layer(name: &str, width: u32, height: u32, data: &[u32]) -> Result<(), TiledError> {
+    if data.len() != (width * height) as usize {
+        return Err(TiledError::Invalid(format!(
+            "layer '{name}' has {} tiles, expected {width}x{height}",
+            data.len()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct RawMap {
+        width: u32,
+        height: u32,
+        tilewidth: f32,
+        tileheight: f32,
+        #[serde(default)]
+        infinite: bool,
+        #[serde(default)]
+        layers: Vec<RawLayer>,
+        #[serde(default)]
+        tilesets: Vec<RawTileset>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawData {
+        Csv(Vec<u32>),
+        Encoded(serde::de::IgnoredAny),
+    }
+
+    fn default_true() -> bool {
+        true
+    }
+    fn default_opacity() -> f32 {
+        1.0
+    }
+
+    #[derive(Deserialize)]
+    struct RawLayer {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        width: u32,
+        #[serde(default)]
+        height: u32,
+        data: Option<RawData>,
+        #[serde(default = "default_true")]
+        visible: bool,
+        #[serde(default = "default_opacity")]
+        opacity: f32,
+        #[serde(default)]
+        offsetx: f32,
+        #[serde(default)]
+        offsety: f32,
+        #[serde(default)]
+        layers: Vec<RawLayer>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawTileset {
+        firstgid: u32,
+        #[serde(default)]
+        name: String,
+        source: Option<String>,
+        image: Option<String>,
+        #[serde(default)]
+        imagewidth: f32,
+        #[serde(default)]
+        imageheight: f32,
+        #[serde(default)]
+        tilewidth: f32,
+        #[serde(default)]
+        tileheight: f32,
+        #[serde(default)]
+        margin: f32,
+        #[serde(default)]
+        spacing: f32,
+    }
+
+    impl From<serde_json::Error> for TiledError {
+        fn from(e: serde_json::Error) -> Self {
+            Self::Json(e)
+        }
+    }
+
+    fn flatten(
+        raw: Vec<RawLayer>,
+        parent: Parent,
+        out: &mut Vec<TiledLayer>,
+    ) -> Result<(), TiledError> {
+        for layer in raw {
+            let state = Parent {
+                visible: parent.visible && layer.visible,
+                opacity: parent.opacity * layer.opacity,
+                offset: parent.offset + vec2(layer.offsetx, layer.offsety),
+            };
+            match layer.kind.as_str() {
+                "group" => flatten(layer.layers, state, out)?,
+                "tilelayer" => {
+                    let data = match layer.data {
+                        Some(RawData::Csv(data)) => data,
+                        Some(RawData::Encoded(_)) => {
+                            return Err(TiledError::Unsupported(format!(
+                                "layer '{}' uses encoded data, export as CSV",
+                                layer.name
+                            )));
+                        }
+                        None => Vec::new(),
+                    };
+                    check_layer(&layer.name, layer.width, layer.height, &data)?;
+                    out.push(TiledLayer {
+                        name: layer.name,
+                        width: layer.width,
+                        height: layer.height,
+                        data,
+                        visible: state.visible,
+                        opacity: state.opacity,
+                        offset: state.offset,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    impl TiledMap {
+        /// Parse a map saved in Tiled's JSON format with CSV layer data
+        pub fn from_json(json: &str) -> Result<Self, TiledError> {
+            let raw: RawMap = serde_json::from_str(json)?;
+            if raw.infinite {
+                return Err(TiledError::Unsupported("infinite maps".into()));
+            }
+
+            let mut tilesets = Vec::new();
+            for ts in raw.tilesets {
+                if let Some(source) = ts.source {
+                    return Err(TiledError::Unsupported(format!(
+                        "external tileset '{source}', embed it in the map"
+                    )));
+                }
+                // Image collection tilesets have no single image
+                let Some(image) = ts.image else {
+                    continue;
+                };
+                tilesets.push(TiledTileset {
+                    first_gid: ts.firstgid,
+                    name: ts.name,
+                    image,
+                    image_size: vec2(ts.imagewidth, ts.imageheight),
+                    tile_size: vec2(ts.tilewidth, ts.tileheight),
+                    margin: ts.margin,
+                    spacing: ts.spacing,
+                });
+            }
+
+            let mut layers = Vec::new();
+            flatten(raw.layers, ROOT, &mut layers)?;
+
+            Ok(Self {
+                width: raw.width,
+                height: raw.height,
+                tile_size: vec2(raw.tilewidth, raw.tileheight),
+                tilesets,
+                layers,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "tmx")]
+mod tmx {
+    use std::str::FromStr;
+
+    use roxmltree::{Document, Node};
+
+    use super::*;
+
+    impl From<roxmltree::Error> for TiledError {
+        fn from(e: roxmltree::Error) -> Self {
+            Self::Xml(e)
+        }
+    }
+
+    fn attr<T: FromStr>(node: Node, name: &str) -> Result<T, TiledError> {
+        let value = node.attribute(name).ok_or_else(|| {
+            TiledError::Invalid(format!("<{}> is missing '{name}'", node.tag_name().name()))
+        })?;
+        value.trim().parse().map_err(|_| {
+            TiledError::Invalid(format!(
+                "<{}> has a malformed '{name}'",
+                node.tag_name().name()
+            ))
+        })
+    }
+
+    fn attr_or<T: FromStr>(node: Node, name: &str, default: T) -> Result<T, TiledError> {
+        match node.attribute(name) {
+            Some(_) => attr(node, name),
+            None => Ok(default),
+        }
+    }
+
+    fn layer_data(layer: Node, name: &str) -> Result<Vec<u32>, TiledError> {
+        let Some(data) = layer.children().find(|n| n.has_tag_name("data")) else {
+            return Ok(Vec::new());
+        };
+        match data.attribute("encoding") {
+            Some("csv") => data
+                .text()
+                .unwrap_or_default()
+                .split(',')
+                .map(|gid| {
+                    gid.trim().parse().map_err(|_| {
+                        TiledError::Invalid(format!("layer '{name}' has a malformed tile id"))
+                    })
+                })
+                .collect(),
+            None => data
+                .children()
+                .filter(|n| n.has_tag_name("tile"))
+                .map(|tile| attr_or(tile, "gid", 0))
+                .collect(),
+            Some(_) => Err(TiledError::Unsupported(format!(
+                "layer '{name}' uses encoded data, export as CSV"
+            ))),
+        }
+    }
+
+    fn flatten(node: Node, parent: Parent, out: &mut Vec<TiledLayer>) -> Result<(), TiledError> {
+        for child in node.children().filter(Node::is_element) {
+            let state = Parent {
+                visible: parent.visible && attr_or(child, "visible", 1u8)? != 0,
+                opacity: parent.opacity * attr_or(child, "opacity", 1.0f32)?,
+                offset: parent.offset
+                    + vec2(
+                        attr_or(child, "offsetx", 0.0)?,
+                        attr_or(child, "offsety", 0.0)?,
+                    ),
+            };
+            match child.tag_name().name() {
+                "group" => flatten(child, state, out)?,
+                "layer" => {
+                    let name = attr_or(child, "name", String::new())?;
+                    let (width, height) = (attr(child, "width")?, attr(child, "height")?);
+                    let data = layer_data(child, &name)?;
+                    check_layer(&name, width, height, &data)?;
+                    out.push(TiledLayer {
+                        name,
+                        width,
+                        height,
+                        data,
+                        visible: state.visible,
+                        opacity: state.opacity,
+                        offset: state.offset,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    impl TiledMap {
+        /// Parse a map saved in Tiled's TMX (XML) format with CSV or XML layer data
+        pub fn from_tmx(xml: &str) -> Result<Self, TiledError> {
+            let doc = Document::parse(xml)?;
+            let map = doc.root_element();
+            if attr_or(map, "infinite", 0u8)? != 0 {
+                return Err(TiledError::Unsupported("infinite maps".into()));
+            }
+
+            let mut tilesets = Vec::new();
+            for ts in map.children().filter(|n| n.has_tag_name("tileset")) {
+                if let Some(source) = ts.attribute("source") {
+                    return Err(TiledError::Unsupported(format!(
+                        "external tileset '{source}', embed it in the map"
+                    )));
+                }
+                // Image collection tilesets have no single image
+                let Some(image) = ts.children().find(|n| n.has_tag_name("image")) else {
+                    continue;
+                };
+                tilesets.push(TiledTileset {
+                    first_gid: attr(ts, "firstgid")?,
+                    name: attr_or(ts, "name", String::new())?,
+                    image: attr(image, "source")?,
+                    image_size: vec2(attr(image, "width")?, attr(image, "height")?),
+                    tile_size: vec2(attr(ts, "tilewidth")?, attr(ts, "tileheight")?),
+                    margin: attr_or(ts, "margin", 0.0)?,
+                    spacing: attr_or(ts, "spacing", 0.0)?,
+                });
+            }
+
+            let mut layers = Vec::new();
+            flatten(map, ROOT, &mut layers)?;
+
+            Ok(Self {
+                width: attr(map, "width")?,
+                height: attr(map, "height")?,
+                tile_size: vec2(attr(map, "tilewidth")?, attr(map, "tileheight")?),
+                tilesets,
+                layers,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn layer_size_is_checked() {
+        assert!(check_layer("a", 2, 2, &[0; 4]).is_ok());
+        assert!(matches!(
+            check_layer("a", 2, 2, &[0; 3]),
+            Err(TiledError::Invalid(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_map() {
+        let json = r#"{
+            "width": 2, "height": 1, "tilewidth": 16, "tileheight": 16,
+            "tilesets": [{
+                "firstgid": 1, "name": "ground", "image": "art/ground.png",
+                "imagewidth": 64, "imageheight": 32, "tilewidth": 16, "tileheight": 16,
+                "margin": 0, "spacing": 0
+            }],
+            "layers": [
+                { "type": "objectgroup", "name": "spawns", "objects": [] },
+                { "type": "group", "name": "g", "opacity": 0.5, "offsetx": 4, "layers": [
+                    { "type": "tilelayer", "name": "floor", "width": 2, "height": 1,
+                      "data": [1, 2147483650], "opacity": 0.5, "visible": true }
+                ]}
+            ]
+        }"#;
+        let map = TiledMap::from_json(json).unwrap();
+        assert_eq!(map.tilesets[0].image_name(), "ground.png");
+
+        let layer = &map.layers[0];
+        assert_eq!(layer.name, "floor");
+        assert_eq!(layer.opacity, 0.25);
+        assert_eq!(layer.offset, vec2(4.0, 0.0));
+        assert!(Tile::from_gid(layer.data[1]).flip_x);
+
+        let tiles = map.tile_layer(layer, &[map.tilesets[0].tileset(7)]);
+        assert_eq!(tiles.tile(1, 0).map(|t| t.id), Some(2));
+    }
+
+    #[cfg(feature = "tmx")]
+    #[test]
+    fn tmx_map() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map version="1.10" orientation="orthogonal" width="2" height="2" tilewidth="8" tileheight="8" infinite="0">
+              <tileset firstgid="3" name="walls" tilewidth="8" tileheight="8" spacing="1" margin="1">
+                <image source="walls.png" width="19" height="19"/>
+              </tileset>
+              <layer id="1" name="csv" width="2" height="2" visible="0">
+                <data encoding="csv">
+            3,0,
+            4,1073741827
+            </data>
+              </layer>
+              <group name="g" offsety="8">
+                <layer id="2" name="xml" width="2" height="2">
+                  <data><tile gid="3"/><tile/><tile/><tile gid="6"/></data>
+                </layer>
+              </group>
+            </map>"#;
+        let map = TiledMap::from_tmx(xml).unwrap();
+        assert_eq!(map.tilesets[0].tileset(0).tile_count(), 4);
+
+        assert_eq!(map.layers[0].data, [3, 0, 4, 0x4000_0003]);
+        assert!(!map.layers[0].visible);
+        assert_eq!(map.layers[1].data, [3, 0, 0, 6]);
+        assert_eq!(map.layers[1].offset, vec2(0.0, 8.0));
+    }
+
+    #[cfg(feature = "tmx")]
+    #[test]
+    fn tmx_rejects_encoded_data() {
+        let xml = r#"<map width="1" height="1" tilewidth="8" tileheight="8">
+              <layer name="b64" width="1" height="1"><data encoding="base64">AQAAAA==</data></layer>
+            </map>"#;
+        assert!(matches!(
+            TiledMap::from_tmx(xml),
+            Err(TiledError::Unsupported(_))
+        ));
+    }
+}
@@ -0,0 +1,463 @@
+use std::ops::Range;
+
+use egor_render::vertex::Vertex;
+use glam::{Vec2, vec2};
+
+use crate::{
+    color::Color,
+    graphics::Graphics,
+    math::Rect,
+    mesh::Mesh,
+    primitives::{flip_uvs, source_uvs},
+};
+
+/// Tiles per chunk side, each chunk is cached as its own geometry
+const CHUNK_TILES: u32 = 32;
+
+/// A grid of equally sized tiles in a texture
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tileset {
+    texture_id: usize,
+    texture_size: Vec2,
+    tile_size: Vec2,
+    margin: f32,
+    spacing: f32,
+    first_id: u32,
+}
+
+impl Tileset {
+    /// Create a tileset from a texture of `texture_size` pixels cut into `tile_size` pixel tiles
+    ///
+    /// Tile ids start at 1 (0 is an empty cell), see [`Self::with_first_id`]
+    pub fn new(texture_id: usize, texture_size: Vec2, tile_size: Vec2) -> Self {
+        Self {
+            texture_id,
+            texture_size,
+            tile_size,
+            margin: 0.0,
+            spacing: 0.0,
+            first_id: 1,
+        }
+    }
+    /// Sets the pixels around the edge of the texture before the first tile
+    pub fn with_margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+    /// Sets the pixels between neighbouring tiles
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    /// Sets the id of the first tile, e.g. a Tiled `firstgid`
+    pub fn with_first_id(mut self, id: u32) -> Self {
+        self.first_id = id.max(1);
+        self
+    }
+
+    pub fn texture_id(&self) -> usize {
+        self.texture_id
+    }
+    pub fn first_id(&self) -> u32 {
+        self.first_id
+    }
+    /// Number of tile columns & rows that fit in the texture
+    pub fn grid(&self) -> (u32, u32) {
+        let step = self.tile_size + self.spacing;
+        let fit = (self.texture_size - 2.0 * self.margin + self.spacing) / step.max(Vec2::ONE);
+        (fit.x.max(0.0) as u32, fit.y.max(0.0) as u32)
+    }
+    /// Number of tiles in the set
+    pub fn tile_count(&self) -> u32 {
+        let (columns, rows) = self.grid();
+        columns * rows
+    }
+    /// Whether `id` belongs to this tileset
+    pub fn contains(&self, id: u32) -> bool {
+        id >= self.first_id && id - self.first_id < self.tile_count()
+    }
+    /// Pixel region of tile `id` in the texture
+    pub fn source_rect(&self, id: u32) -> Option<Rect> {
+        if !self.contains(id) {
+            return None;
+        }
+        let (columns, _) = self.grid();
+        let local = id - self.first_id;
+        let cell = vec2((local % columns) as f32, (local / columns) as f32);
+        let position = Vec2::splat(self.margin) + cell * (self.tile_size + self.spacing);
+        Some(Rect::new(position, self.tile_size))
+    }
+}
+
+/// A single cell of a [`TileLayer`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    /// Tileset id, 0 is an empty cell & [`Tile::SOLID`] an untextured cell
+    pub id: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Swaps the x & y axes, applied before the other flips (90° rotations)
+    pub flip_diagonal: bool,
+    /// Tint, or the fill of a solid cell
+    pub color: Color,
+}
+
+impl Default for Tile {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl Tile {
+    pub const EMPTY: Tile = Tile::new(0);
+    /// Id of cells filled with their color instead of a texture
+    pub const SOLID: u32 = u32::MAX;
+
+    const FLIP_X: u32 = 0x8000_0000;
+    const FLIP_Y: u32 = 0x4000_0000;
+    const FLIP_DIAGONAL: u32 = 0x2000_0000;
+    /// Hex rotation flag, unused for orthogonal maps but masked off
+    const ROTATE_HEX: u32 = 0x1000_0000;
+
+    pub const fn new(id: u32) -> Self {
+        Self {
+            id,
+            flip_x: false,
+            flip_y: false,
+            flip_diagonal: false,
+            color: Color::WHITE,
+        }
+    }
+    /// An untextured cell filled with `color`
+    pub const fn solid(color: Color) -> Self {
+        Self {
+            color,
+            ..Self::new(Self::SOLID)
+        }
+    }
+    /// Decode a Tiled global tile id, flip flags stored in the high bits
+    pub fn from_gid(gid: u32) -> Self {
+        Self {
+            flip_x: gid & Self::FLIP_X != 0,
+            flip_y: gid & Self::FLIP_Y != 0,
+            flip_diagonal: gid & Self::FLIP_DIAGONAL != 0,
+            ..Self::new(
+                gid & !(Self::FLIP_X | Self::FLIP_Y | Self::FLIP_DIAGONAL | Self::ROTATE_HEX),
+            )
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.id == 0
+    }
+}
+
+/// Cached geometry of one chunk, a mesh per texture (`None` for solid cells)
+#[derive(Clone, Debug, Default)]
+struct Chunk {
+    dirty: bool,
+    meshes: Vec<(Option<usize>, Mesh)>,
+}
+
+/// A grid of tiles drawn from one or more [`Tileset`]s
+///
+/// Tiles are stretched to the layer's tile size. The layer is split into chunks
+/// that are only rebuilt after their tiles change & only drawn when they
+/// overlap the camera viewport
+#[derive(Clone, Debug)]
+pub struct TileLayer {
+    width: u32,
+    height: u32,
+    tile_size: Vec2,
+    tiles: Vec<Tile>,
+    tilesets: Vec<Tileset>,
+    position: Vec2,
+    color: Color,
+    visible: bool,
+    chunks: Vec<Chunk>,
+}
+
+impl TileLayer {
+    /// Create an empty layer of `width` x `height` tiles, each `tile_size` world units
+    pub fn new(width: u32, height: u32, tile_size: Vec2) -> Self {
+        let chunk_count = width.div_ceil(CHUNK_TILES) * height.div_ceil(CHUNK_TILES);
+        Self {
+            width,
+            height,
+            tile_size,
+            tiles: vec![Tile::EMPTY; (width * height) as usize],
+            tilesets: Vec::new(),
+            position: Vec2::ZERO,
+            color: Color::WHITE,
+            visible: true,
+            chunks: vec![
+                Chunk {
+                    dirty: true,
+                    meshes: Vec::new(),
+                };
+                chunk_count as usize
+            ],
+        }
+    }
+    /// Adds a tileset used to resolve tile ids
+    pub fn with_tileset(mut self, tileset: Tileset) -> Self {
+        self.add_tileset(tileset);
+        self
+    }
+    /// Adds a tileset used to resolve tile ids
+    pub fn add_tileset(&mut self, tileset: Tileset) {
+        self.tilesets.push(tileset);
+        self.mark_all_dirty();
+    }
+
+    /// Layer size in tiles
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+    /// Size of a tile in world units
+    pub fn tile_size(&self) -> Vec2 {
+        self.tile_size
+    }
+    /// Sets the world-space position of the layer's top-left corner
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+    /// Tints the whole layer, e.g. for layer opacity
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Tile at a cell, `None` outside the layer
+    pub fn tile(&self, x: u32, y: u32) -> Option<Tile> {
+        (x < self.width && y < self.height).then(|| self.tiles[(y * self.width + x) as usize])
+    }
+    /// Sets a cell, ignored outside the layer
+    pub fn set_tile(&mut self, x: u32, y: u32, tile: Tile) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let cell = &mut self.tiles[(y * self.width + x) as usize];
+        if *cell != tile {
+            *cell = tile;
+            let chunk = self.chunk_index(x / CHUNK_TILES, y / CHUNK_TILES);
+            self.chunks[chunk].dirty = true;
+        }
+    }
+    /// Replaces every cell row by row, extra tiles are ignored
+    pub fn set_tiles(&mut self, tiles: impl IntoIterator<Item = Tile>) {
+        for (cell, tile) in self.tiles.iter_mut().zip(tiles) {
+            *cell = tile;
+        }
+        self.mark_all_dirty();
+    }
+    /// Cell containing a world-space point, `None` outside the layer
+    pub fn world_to_tile(&self, world: Vec2) -> Option<(u32, u32)> {
+        let cell = ((world - self.position) / self.tile_size).floor();
+        (cell.x >= 0.0
+            && cell.y >= 0.0
+            && (cell.x as u32) < self.width
+            && (cell.y as u32) < self.height)
+            .then_some((cell.x as u32, cell.y as u32))
+    }
+    /// World-space top-left corner of a cell
+    pub fn tile_to_world(&self, x: u32, y: u32) -> Vec2 {
+        self.position + vec2(x as f32, y as f32) * self.tile_size
+    }
+
+    /// Draws the chunks overlapping the camera viewport, rebuilding changed ones
+    pub fn draw(&mut self, gfx: &mut Graphics) {
+        if !self.visible {
+            return;
+        }
+        let screen = gfx.screen_size();
+        let view = gfx.camera().viewport(screen);
+        let (columns, rows) = self.visible_chunks(&view);
+
+        for cy in rows {
+            for cx in columns.clone() {
+                let index = self.chunk_index(cx, cy);
+                if self.chunks[index].dirty {
+                    self.chunks[index] = Chunk {
+                        dirty: false,
+                        meshes: self.build_chunk(cx, cy),
+                    };
+                }
+                for (texture, mesh) in &self.chunks[index].meshes {
+                    let builder = gfx.mesh(mesh).at(self.position).tint(self.color);
+                    if let Some(id) = texture {
+                        builder.texture(*id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn chunk_index(&self, cx: u32, cy: u32) -> usize {
+        (cy * self.width.div_ceil(CHUNK_TILES) + cx) as usize
+    }
+
+    fn mark_all_dirty(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.dirty = true;
+        }
+    }
+
+    /// Chunk column & row ranges overlapping a world-space rect
+    fn visible_chunks(&self, view: &Rect) -> (Range<u32>, Range<u32>) {
+        let chunk_size = self.tile_size * CHUNK_TILES as f32;
+        let min = ((view.min() - self.position) / chunk_size)
+            .floor()
+            .max(Vec2::ZERO);
+        let max = ((view.max() - self.position) / chunk_size)
+            .ceil()
+            .max(Vec2::ZERO);
+        let (w, h) = (
+            self.width.div_ceil(CHUNK_TILES),
+            self.height.div_ceil(CHUNK_TILES),
+        );
+        (
+            (min.x as u32).min(w)..(max.x as u32).min(w),
+            (min.y as u32).min(h)..(max.y as u32).min(h),
+        )
+    }
+
+    /// Builds chunk geometry in layer space, tiles without a matching tileset are skipped
+    fn build_chunk(&self, cx: u32, cy: u32) -> Vec<(Option<usize>, Mesh)> {
+        let mut meshes: Vec<(Option<usize>, Mesh)> = Vec::new();
+        let x_range = cx * CHUNK_TILES..((cx + 1) * CHUNK_TILES).min(self.width);
+        let y_range = cy * CHUNK_TILES..((cy + 1) * CHUNK_TILES).min(self.height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let tile = self.tiles[(y * self.width + x) as usize];
+                let (texture, uvs) = match tile.id {
+                    0 => continue,
+                    Tile::SOLID => (None, [[0.0; 2]; 4]),
+                    id => {
+                        let Some((tileset, source)) = self
+                            .tilesets
+                            .iter()
+                            .find_map(|t| Some((t, t.source_rect(id)?)))
+                        else {
+                            continue;
+                        };
+                        (Some(tileset.texture_id), tile_uvs(&tile, &source, tileset))
+                    }
+                };
+
+                let mesh = match meshes.iter().position(|(t, _)| *t == texture) {
+                    Some(i) => &mut meshes[i].1,
+                    None => {
+                        meshes.push((texture, Mesh::new()));
+                        &mut meshes.last_mut().unwrap().1
+                    }
+                };
+                let cell = Rect::new(vec2(x as f32, y as f32) * self.tile_size, self.tile_size);
+                let color = tile.color.components();
+                let corners = cell.corners();
+                let vertices: [Vertex; 4] =
+                    std::array::from_fn(|i| Vertex::new(corners[i].into(), color, uvs[i]));
+                mesh.push(&vertices, &[0, 1, 2, 2, 3, 0]);
+            }
+        }
+        meshes
+    }
+}
+
+/// UV corners (TL, TR, BR, BL) of a tile, diagonal flip first then x & y flips
+fn tile_uvs(tile: &Tile, source: &Rect, tileset: &Tileset) -> [[f32; 2]; 4] {
+    let [tl, tr, br, bl] = source_uvs(source, tileset.texture_size);
+    let uvs = if tile.flip_diagonal {
+        [tl, bl, br, tr]
+    } else {
+        [tl, tr, br, bl]
+    };
+    flip_uvs(uvs, tile.flip_x, tile.flip_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tileset_margin_spacing() {
+        // 2px margin, 1px spacing: 3x2 tiles of 10px fit in 36x25px
+        let set = Tileset::new(0, vec2(36.0, 25.0), vec2(10.0, 10.0))
+            .with_margin(2.0)
+            .with_spacing(1.0)
+            .with_first_id(5);
+        assert_eq!(set.grid(), (3, 2));
+        assert_eq!(
+            set.source_rect(5 + 4),
+            Some(Rect::new(vec2(13.0, 13.0), vec2(10.0, 10.0)))
+        );
+        assert_eq!(set.source_rect(4), None);
+        assert_eq!(set.source_rect(11), None);
+    }
+
+    #[test]
+    fn gid_flags() {
+        let tile = Tile::from_gid(0xA000_0007);
+        assert_eq!(tile.id, 7);
+        assert!(tile.flip_x && tile.flip_diagonal && !tile.flip_y);
+    }
+
+    #[test]
+    fn diagonal_flip_transposes_uvs() {
+        let set = Tileset::new(0, vec2(1.0, 1.0), vec2(1.0, 1.0));
+        let source = Rect::new(Vec2::ZERO, Vec2::ONE);
+        let tile = Tile {
+            flip_diagonal: true,
+            ..Tile::new(1)
+        };
+        assert_eq!(
+            tile_uvs(&tile, &source, &set),
+            [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn chunks_group_tiles_by_texture() {
+        let set = Tileset::new(3, vec2(32.0, 32.0), vec2(16.0, 16.0));
+        let mut layer = TileLayer::new(40, 40, vec2(8.0, 8.0)).with_tileset(set);
+        layer.set_tile(0, 0, Tile::new(1));
+        layer.set_tile(1, 0, Tile::new(4));
+        layer.set_tile(2, 0, Tile::solid(Color::RED));
+        layer.set_tile(3, 0, Tile::new(99)); // no tileset, skipped
+        layer.set_tile(33, 0, Tile::new(1)); // next chunk
+
+        let meshes = layer.build_chunk(0, 0);
+        assert_eq!(meshes.len(), 2);
+        assert_eq!(meshes[0].0, Some(3));
+        assert_eq!(meshes[0].1.vertex_count(), 8);
+        assert_eq!(meshes[1].0, None);
+    }
+
+    #[test]
+    fn culls_to_view() {
+        let layer = TileLayer::new(100, 100, vec2(10.0, 10.0));
+        // chunks are 320 units, the view covers the second & third columns
+        let view = Rect::new(vec2(400.0, -50.0), vec2(300.0, 100.0));
+        assert_eq!(layer.visible_chunks(&view), (1..3, 0..1));
+
+        let outside = Rect::new(vec2(-500.0, 0.0), vec2(100.0, 100.0));
+        assert!(layer.visible_chunks(&outside).0.is_empty());
+    }
+
+    #[test]
+    fn world_tile_round_trip() {
+        let mut layer = TileLayer::new(4, 4, vec2(16.0, 16.0));
+        layer.set_position(vec2(100.0, 0.0));
+        assert_eq!(layer.world_to_tile(vec2(120.0, 40.0)), Some((1, 2)));
+        assert_eq!(layer.tile_to_world(1, 2), vec2(116.0, 32.0));
+        assert_eq!(layer.world_to_tile(vec2(99.0, 0.0)), None);
+    }
+}
@@ -1,7 +1,15 @@
-struct Camera {
+// Prepended by egor's shader preprocessor, a custom shader only needs `fs_main`
+
+struct Globals {
     view_proj: mat4x4<f32>,
+    time: f32,
+    delta: f32,
+    frame: u32,
+    zoom: f32,
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
 }
-@group(1) @binding(0) var<uniform> camera: Camera;
+@group(1) @binding(0) var<uniform> globals: Globals;
 @group(0) @binding(0) var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1) var s_diffuse: sampler;
 
@@ -20,8 +28,8 @@ struct VertexOutput {
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
-    output.clip_position = camera.view_proj * vec4<f32>(input.position, 0.0, 1.0);
+    output.clip_position = globals.view_proj * vec4<f32>(input.position, 0.0, 1.0);
     output.color = input.color;
     output.tex_coords = input.tex_coords;
     return output;
-}
\ No newline at end of file
+}
@@ -1,5 +1,32 @@
+/// Per-slot uniform bound at `@group(1) @binding(0)` of every pipeline
+///
+/// Besides the view-projection matrix it carries frame globals, so custom
+/// shaders can animate without their own uniform buffers  
+/// Laid out like `Globals` in [`crate::preprocess::PRELUDE`]
 #[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
     pub view_proj: [[f32; 4]; 4],
+    /// Seconds since the app started
+    pub time: f32,
+    /// Seconds since the previous frame
+    pub delta: f32,
+    /// Index of the current frame
+    pub frame: u32,
+    /// Screen pixels per world unit of the slot's camera
+    pub zoom: f32,
+    /// Size in pixels of the area the slot draws into
+    pub resolution: [f32; 2],
+    /// Mouse position in pixels, relative to the area the slot draws into
+    pub mouse: [f32; 2],
+}
+
+impl CameraUniform {
+    /// Uniform with only a view-projection matrix, other fields zeroed
+    pub fn new(view_proj: [[f32; 4]; 4]) -> Self {
+        Self {
+            view_proj,
+            ..bytemuck::Zeroable::zeroed()
+        }
+    }
 }
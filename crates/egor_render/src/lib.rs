@@ -2,15 +2,22 @@ pub mod camera;
 pub mod frame;
 pub mod geometry_batch;
 pub mod pipeline;
+pub mod preprocess;
 mod renderer;
+pub mod shader;
 pub mod target;
 pub mod texture;
+pub mod uniform;
 pub mod vertex;
 
 pub use camera::CameraUniform;
 pub use frame::{Frame, Presentable};
 pub use geometry_batch::GeometryBatch;
-pub use renderer::Renderer;
+pub use preprocess::{Preprocessor, SourceMap};
+pub use renderer::{DrawParams, PARAMS_BLOCK_SIZE, Renderer, Viewport};
+pub use shader::{MAX_SHADER_TEXTURES, ShaderBindings, ShaderDiagnostic, ShaderError};
 pub use target::{Backbuffer, RenderTarget};
+pub use uniform::Uniform;
 
-pub use wgpu::{Device, Queue, RenderPass, TextureFormat};
+pub use image::ImageError;
+pub use wgpu::{Device, FilterMode, Queue, RenderPass, TextureFormat};
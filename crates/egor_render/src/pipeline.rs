@@ -1,11 +1,19 @@
 use wgpu::{
     BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BlendState,
     ColorTargetState, ColorWrites, Device, FragmentState, PipelineLayoutDescriptor, RenderPipeline,
-    RenderPipelineDescriptor, SamplerBindingType, ShaderStages, TextureFormat, TextureSampleType,
-    TextureViewDimension, VertexState, include_wgsl,
+    RenderPipelineDescriptor, SamplerBindingType, ShaderModuleDescriptor, ShaderSource,
+    ShaderStages, TextureFormat, TextureSampleType, TextureViewDimension, VertexState,
 };
 
-use crate::vertex::Vertex;
+use crate::{
+    camera::CameraUniform,
+    preprocess::Preprocessor,
+    shader::{FRAGMENT_ENTRY, VERTEX_ENTRY},
+    vertex::Vertex,
+};
+
+/// Fragment stage of the primitive pipeline, the rest comes from [`crate::preprocess::PRELUDE`]
+pub(crate) const DEFAULT_SHADER: &str = include_str!("../shader.wgsl");
 
 /// Contains all render pipelines and bind group layouts for [`crate::Renderer`]
 ///
@@ -13,11 +21,13 @@ use crate::vertex::Vertex;
 /// - The main primitive rendering pipeline (textured quads, sprites, shapes)
 /// - Texture bind group layout (for sampling textures in shaders)
 /// - Camera bind group layout (for view/projection transforms)
+/// - Params bind group layout (for per-draw shader parameters)
 pub struct Pipelines {
     pub primitive: RenderPipeline,
     pub custom: Vec<RenderPipeline>,
     pub texture_layout: BindGroupLayout,
     pub camera_layout: BindGroupLayout,
+    pub params_layout: BindGroupLayout,
 }
 
 impl Pipelines {
@@ -25,6 +35,7 @@ impl Pipelines {
     pub fn new(device: &Device, surface_format: TextureFormat) -> Self {
         let texture_layout = create_texture_bind_group_layout(device);
         let camera_layout = create_camera_bind_group_layout(device);
+        let params_layout = create_params_bind_group_layout(device);
 
         let primitive =
             create_primitive_pipeline(device, surface_format, &texture_layout, &camera_layout);
@@ -34,6 +45,7 @@ impl Pipelines {
             custom: Vec::new(),
             texture_layout,
             camera_layout,
+            params_layout,
         }
     }
 
@@ -45,16 +57,28 @@ impl Pipelines {
         wgsl_source: &str,
         uniform_layouts: &[&BindGroupLayout],
     ) -> usize {
-        let pipeline = create_custom_pipeline(
+        let pipeline =
+            self.create_custom_pipeline(device, surface_format, wgsl_source, uniform_layouts);
+        self.custom.push(pipeline);
+        self.custom.len() - 1
+    }
+
+    /// Creates a custom shader pipeline without registering it
+    pub fn create_custom_pipeline(
+        &self,
+        device: &Device,
+        surface_format: TextureFormat,
+        wgsl_source: &str,
+        uniform_layouts: &[&BindGroupLayout],
+    ) -> RenderPipeline {
+        create_custom_pipeline(
             device,
             surface_format,
             &self.texture_layout,
             &self.camera_layout,
             uniform_layouts,
             wgsl_source,
-        );
-        self.custom.push(pipeline);
-        self.custom.len() - 1
+        )
     }
 
     /// Get a custom pipeline by index
@@ -95,16 +119,38 @@ fn create_texture_bind_group_layout(device: &Device) -> BindGroupLayout {
 /// Creates the bind group layout for camera uniforms
 ///
 /// Defines a single binding:
-/// - Binding 0: Uniform buffer containing view-projection matrix (vertex shader)
+/// - Binding 0: Uniform buffer containing view-projection matrix & frame globals
+///   (vertex & fragment shaders), bound at a dynamic offset to select one of several cameras
 fn create_camera_bind_group_layout(device: &Device) -> BindGroupLayout {
     device.create_bind_group_layout(&BindGroupLayoutDescriptor {
         label: Some("Camera Bind Group Layout"),
         entries: &[BindGroupLayoutEntry {
             binding: 0,
-            visibility: ShaderStages::VERTEX,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: wgpu::BufferSize::new(size_of::<CameraUniform>() as u64),
+            },
+            count: None,
+        }],
+    })
+}
+
+/// Creates the bind group layout for per-draw shader parameters
+///
+/// Defines a single binding:
+/// - Binding 0: Uniform buffer of parameter blocks (vertex & fragment shaders),
+///   bound at a dynamic offset to select the block of each draw
+fn create_params_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: Some("Params Bind Group Layout"),
+        entries: &[BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
             ty: BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
-                has_dynamic_offset: false,
+                has_dynamic_offset: true,
                 min_binding_size: None,
             },
             count: None,
@@ -125,7 +171,13 @@ fn create_primitive_pipeline(
     texture_layout: &BindGroupLayout,
     camera_layout: &BindGroupLayout,
 ) -> RenderPipeline {
-    let shader = device.create_shader_module(include_wgsl!("../shader.wgsl"));
+    let source = Preprocessor::new()
+        .process(DEFAULT_SHADER)
+        .expect("default shader has no directives to fail");
+    let shader = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("Primitive Shader"),
+        source: ShaderSource::Wgsl(source.into()),
+    });
 
     let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
         label: Some("Primitive Pipeline Layout"),
@@ -138,7 +190,7 @@ fn create_primitive_pipeline(
         layout: Some(&pipeline_layout),
         vertex: VertexState {
             module: &shader,
-            entry_point: Some("vs_main"),
+            entry_point: Some(VERTEX_ENTRY),
             buffers: &[Vertex::desc()],
             compilation_options: Default::default(),
         },
@@ -147,7 +199,7 @@ fn create_primitive_pipeline(
         multisample: Default::default(),
         fragment: Some(FragmentState {
             module: &shader,
-            entry_point: Some("fs_main"),
+            entry_point: Some(FRAGMENT_ENTRY),
             targets: &[Some(ColorTargetState {
                 format: surface_format,
                 blend: Some(BlendState::ALPHA_BLENDING),
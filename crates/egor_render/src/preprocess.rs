@@ -0,0 +1,362 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::shader::{ShaderDiagnostic, ShaderError};
+
+/// Egor's standard globals, texture bindings, vertex input/output & `vs_main`
+pub const PRELUDE: &str = include_str!("../prelude.wgsl");
+/// Name errors in [`PRELUDE`] are reported under
+const PRELUDE_NAME: &str = "egor prelude";
+
+type Loader<'a> = Box<dyn Fn(&str) -> Option<String> + 'a>;
+
+/// Expands directives in WGSL before it's loaded as a custom shader
+///
+/// Supports `#include "file"` resolved with [`Self::with_loader`] (each file is included
+/// once), `#define NAME [value]`, `#undef`, `#ifdef`, `#ifndef`, `#else` & `#endif`
+/// Defined values replace matching identifiers in the lines after them
+/// [`PRELUDE`] is prepended unless disabled, so a shader only needs `fs_main`
+pub struct Preprocessor<'a> {
+    loader: Option<Loader<'a>>,
+    defines: HashMap<String, String>,
+    prelude: bool,
+}
+
+impl Default for Preprocessor<'_> {
+    fn default() -> Self {
+        Self {
+            loader: None,
+            defines: HashMap::new(),
+            prelude: true,
+        }
+    }
+}
+
+/// An open `#ifdef`/`#ifndef` block
+struct Conditional {
+    line: usize,
+    active: bool,
+    parent_active: bool,
+    seen_else: bool,
+}
+
+/// State shared across a source & everything it includes
+struct Expansion {
+    defines: HashMap<String, String>,
+    included: HashSet<String>,
+    output: String,
+    map: SourceMap,
+}
+
+impl Expansion {
+    fn push_line(&mut self, line: &str, file: Option<&str>, number: usize) {
+        substitute(line, &self.defines, &mut self.output);
+        self.output.push('\n');
+        self.map.push(file, number as u32);
+    }
+}
+
+/// Where each line of [`Preprocessor`] output came from, to point errors at the lines
+/// the user wrote rather than the expanded WGSL
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Included files in order of first use
+    files: Vec<String>,
+    /// Index into `files` (`None` for the processed source) & 1-based line of each output line
+    lines: Vec<(Option<usize>, u32)>,
+}
+
+impl SourceMap {
+    fn push(&mut self, file: Option<&str>, line: u32) {
+        let file = file.map(|file| match self.files.iter().position(|f| f == file) {
+            Some(i) => i,
+            None => {
+                self.files.push(file.to_string());
+                self.files.len() - 1
+            }
+        });
+        self.lines.push((file, line));
+    }
+
+    /// File (`None` for the processed source) & 1-based line an output line came from
+    pub fn lookup(&self, line: u32) -> Option<(Option<&str>, u32)> {
+        let &(file, line) = self.lines.get((line as usize).checked_sub(1)?)?;
+        Some((file.map(|i| self.files[i].as_str()), line))
+    }
+
+    /// Points a parse or validation error at the line it came from, naming the file
+    /// if it was included  
+    /// Other errors & an empty map leave it unchanged
+    pub fn remap(&self, mut error: ShaderError) -> ShaderError {
+        if let ShaderError::Parse(diagnostic) | ShaderError::Validation(diagnostic) = &mut error
+            && let Some((file, line)) = diagnostic.location.and_then(|(l, _)| self.lookup(l))
+        {
+            diagnostic.relocate(line, file);
+        }
+        error
+    }
+}
+
+impl<'a> Preprocessor<'a> {
+    /// Preprocessor that prepends [`PRELUDE`] & has no loader or defines
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves `#include` paths to source, `None` if the file doesn't exist
+    pub fn with_loader(mut self, loader: impl Fn(&str) -> Option<String> + 'a) -> Self {
+        self.loader = Some(Box::new(loader));
+        self
+    }
+    /// Defines `name` before the source is read, as if it started with `#define name value`
+    pub fn define(mut self, name: &str, value: &str) -> Self {
+        self.defines.insert(name.to_string(), value.to_string());
+        self
+    }
+    /// Skips [`PRELUDE`], for shaders declaring their own vertex stage & bindings
+    pub fn without_prelude(mut self) -> Self {
+        self.prelude = false;
+        self
+    }
+
+    /// Expands `source` into plain WGSL
+    pub fn process(&self, source: &str) -> Result<String, ShaderError> {
+        self.process_mapped(source).map(|(wgsl, _)| wgsl)
+    }
+
+    /// Like [`Self::process`], also returning where each output line came from so errors
+    /// can be pointed back at `source` & its includes with [`SourceMap::remap`]
+    pub fn process_mapped(&self, source: &str) -> Result<(String, SourceMap), ShaderError> {
+        let mut expansion = Expansion {
+            defines: HashMap::new(),
+            included: HashSet::new(),
+            output: String::new(),
+            map: SourceMap::default(),
+        };
+        if self.prelude {
+            for (i, line) in PRELUDE.lines().enumerate() {
+                expansion.push_line(line, Some(PRELUDE_NAME), i + 1);
+            }
+        }
+        expansion.defines = self.defines.clone();
+        self.expand(&mut expansion, source, None)?;
+        Ok((expansion.output, expansion.map))
+    }
+
+    fn expand(
+        &self,
+        expansion: &mut Expansion,
+        source: &str,
+        file: Option<&str>,
+    ) -> Result<(), ShaderError> {
+        let error = |message: String, line: usize| {
+            let message = match file {
+                Some(file) => format!("{message} in `{file}`"),
+                None => message,
+            };
+            ShaderError::Preprocess(ShaderDiagnostic::at_line(message, source, line as u32))
+        };
+        let mut conditionals: Vec<Conditional> = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let number = i + 1;
+            let active = conditionals.last().is_none_or(|c| c.active);
+            let Some(directive) = line.trim_start().strip_prefix('#') else {
+                if active {
+                    expansion.push_line(line, file, number);
+                }
+                continue;
+            };
+            let (name, arg) = directive
+                .split_once(char::is_whitespace)
+                .map_or((directive, ""), |(name, arg)| (name, arg.trim()));
+
+            match name {
+                "ifdef" | "ifndef" => {
+                    let defined = expansion.defines.contains_key(arg);
+                    conditionals.push(Conditional {
+                        line: number,
+                        active: active && defined == (name == "ifdef"),
+                        parent_active: active,
+                        seen_else: false,
+                    });
+                }
+                "else" => {
+                    let Some(c) = conditionals.last_mut().filter(|c| !c.seen_else) else {
+                        return Err(error("`#else` without a matching `#ifdef`".into(), number));
+                    };
+                    c.active = c.parent_active && !c.active;
+                    c.seen_else = true;
+                }
+                "endif" => {
+                    if conditionals.pop().is_none() {
+                        return Err(error("`#endif` without a matching `#ifdef`".into(), number));
+                    }
+                }
+                _ if !active => {}
+                "define" => {
+                    let (key, value) = arg
+                        .split_once(char::is_whitespace)
+                        .map_or((arg, ""), |(key, value)| (key, value.trim()));
+                    if !is_identifier(key) {
+                        return Err(error(format!("invalid `#define` name `{key}`"), number));
+                    }
+                    expansion.defines.insert(key.to_string(), value.to_string());
+                }
+                "undef" => {
+                    expansion.defines.remove(arg);
+                }
+                "include" => {
+                    let Some(path) = arg.strip_prefix('"').and_then(|a| a.strip_suffix('"')) else {
+                        return Err(error("expected `#include \"file\"`".into(), number));
+                    };
+                    if !expansion.included.insert(path.to_string()) {
+                        continue;
+                    }
+                    let Some(included) = self.loader.as_ref().and_then(|load| load(path)) else {
+                        return Err(error(format!("can't include `{path}`"), number));
+                    };
+                    self.expand(expansion, &included, Some(path))?;
+                }
+                _ => return Err(error(format!("unknown directive `#{name}`"), number)),
+            }
+        }
+
+        match conditionals.last() {
+            Some(c) => Err(error("`#ifdef` without a matching `#endif`".into(), c.line)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Appends `line` to `output`, replacing identifiers defined with a value
+fn substitute(line: &str, defines: &HashMap<String, String>, output: &mut String) {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut rest = line;
+    while let Some(start) = rest.find(is_word) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+        // numbers like `0x1F` or `1e5` are words too, but never identifiers
+        let word = &rest[..end];
+        match defines.get(word).filter(|value| !value.is_empty()) {
+            Some(value) => output.push_str(value),
+            None => output.push_str(word),
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+}
+
+#[cfg(test)]
+mod tests {
+    use naga::proc::Layouter;
+
+    use super::*;
+    use crate::{camera::CameraUniform, pipeline::DEFAULT_SHADER, shader::check_bindings};
+
+    fn process(source: &str) -> Result<String, ShaderError> {
+        Preprocessor::new().without_prelude().process(source)
+    }
+
+    #[test]
+    fn defines_select_branches() {
+        let source = "#define STEPS 4\n#ifdef STEPS\nlet n = STEPS;\n#else\nlet n = 1;\n#endif\n";
+        assert_eq!(process(source).unwrap(), "let n = 4;\n");
+
+        let source = "#ifndef FAST\nslow();\n#else\nfast();\n#endif\n";
+        let fast = Preprocessor::new().without_prelude().define("FAST", "");
+        assert_eq!(fast.process(source).unwrap(), "fast();\n");
+        assert_eq!(process(source).unwrap(), "slow();\n");
+    }
+
+    #[test]
+    fn substitutes_whole_identifiers() {
+        let source = "#define N 8\n#define e5 2\nlet N_2 = N * vec2(N) + 1e5;\n";
+        assert_eq!(process(source).unwrap(), "let N_2 = 8 * vec2(8) + 1e5;\n");
+    }
+
+    #[test]
+    fn includes_each_file_once() {
+        let files = HashMap::from([
+            ("noise.wgsl", "#include \"hash.wgsl\"\nfn noise() {}"),
+            ("hash.wgsl", "fn hash() {}"),
+        ]);
+        let preprocessor = Preprocessor::new()
+            .without_prelude()
+            .with_loader(|path| files.get(path).map(|s| s.to_string()));
+        let source = "#include \"noise.wgsl\"\n#include \"hash.wgsl\"\nfn main() {}";
+        assert_eq!(
+            preprocessor.process(source).unwrap(),
+            "fn hash() {}\nfn noise() {}\nfn main() {}\n"
+        );
+    }
+
+    #[test]
+    fn errors_point_at_directive() {
+        let Err(ShaderError::Preprocess(diagnostic)) =
+            process("fn a() {}\n#include \"missing.wgsl\"")
+        else {
+            panic!("expected a preprocess error");
+        };
+        assert_eq!(diagnostic.location, Some((2, 1)));
+        assert!(diagnostic.message.contains("missing.wgsl"));
+
+        assert!(process("#ifdef A\n").is_err());
+        assert!(process("#endif\n").is_err());
+        assert!(process("#pragma once\n").is_err());
+    }
+
+    #[test]
+    fn errors_map_back_to_source_lines() {
+        let files = HashMap::from([("noise.wgsl", "fn noise() -> f32 {\n    return 0.0\n}")]);
+        let preprocessor =
+            Preprocessor::new().with_loader(|path| files.get(path).map(|s| s.to_string()));
+
+        // the prelude & directives shift the line naga reports
+        let source = "#define N 2\n\nfn f() -> f32 { return 1.0 + ; }";
+        let (wgsl, map) = preprocessor.process_mapped(source).unwrap();
+        let ShaderError::Parse(diagnostic) = map.remap(check_bindings(&wgsl, &[], 0).unwrap_err())
+        else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(diagnostic.location.map(|(line, _)| line), Some(3));
+        assert!(diagnostic.snippet.starts_with("3 | fn f()"));
+
+        let (wgsl, map) = preprocessor
+            .process_mapped("#include \"noise.wgsl\"")
+            .unwrap();
+        let ShaderError::Parse(diagnostic) = map.remap(check_bindings(&wgsl, &[], 0).unwrap_err())
+        else {
+            panic!("expected a parse error");
+        };
+        assert!(diagnostic.message.ends_with("in `noise.wgsl`"));
+        assert_eq!(diagnostic.location.map(|(line, _)| line), Some(3));
+    }
+
+    #[test]
+    fn prelude_only_needs_fs_main() {
+        let source = Preprocessor::new().process(DEFAULT_SHADER).unwrap();
+        assert_eq!(check_bindings(&source, &[], 0), Ok(()));
+    }
+
+    #[test]
+    fn prelude_globals_match_camera_uniform() {
+        let module = naga::front::wgsl::parse_str(PRELUDE).unwrap();
+        let mut layouter = Layouter::default();
+        layouter.update(module.to_ctx()).unwrap();
+        let (globals, _) = module
+            .types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("Globals"))
+            .unwrap();
+        assert_eq!(layouter[globals].size as usize, size_of::<CameraUniform>());
+    }
+}
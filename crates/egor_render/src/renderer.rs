@@ -1,9 +1,18 @@
+use std::{
+    collections::BTreeMap,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use bytemuck::Pod;
+use image::ImageError;
 use wgpu::{
     Adapter, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
-    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, Buffer, BufferUsages, Color,
-    CommandEncoder, Device, DeviceDescriptor, Instance, LoadOp, Operations, Queue, RenderPass,
-    RenderPassColorAttachment, RenderPassDescriptor, RequestAdapterOptions, ShaderStages, StoreOp,
-    SurfaceTarget, TextureFormat, TextureView, WindowHandle,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBinding, BufferDescriptor, BufferSize, BufferUsages, Color, CommandEncoder, Device,
+    DeviceDescriptor, ErrorFilter, Instance, LoadOp, Operations, Queue, RenderPass,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RequestAdapterOptions,
+    ShaderStages, StoreOp, SurfaceTarget, TextureFormat, TextureView, WindowHandle,
     util::{BufferInitDescriptor, DeviceExt, new_instance_with_webgpu_detection},
 };
 
@@ -12,8 +21,11 @@ use crate::{
     frame::Frame,
     geometry_batch::GeometryBatch,
     pipeline::Pipelines,
+    preprocess::SourceMap,
+    shader::{self, MAX_SHADER_TEXTURES, ShaderBindings, ShaderDiagnostic, ShaderError},
     target::{OffscreenTarget, RenderTarget},
     texture::Texture,
+    uniform::Uniform,
 };
 
 pub(crate) struct Gpu {
@@ -29,20 +41,171 @@ pub(crate) struct UniformEntry {
     pub layout: BindGroupLayout,
 }
 
+/// Largest per-draw parameter block in bytes, see [`Renderer::push_params`]
+pub const PARAMS_BLOCK_SIZE: u64 = 256;
+
+/// Per-batch state for [`Renderer::draw`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawParams {
+    /// Texture to sample, the default white texture when `None`
+    pub texture_id: Option<usize>,
+    /// Samples the texture with wrapping UVs instead of clamping them to its edges
+    pub repeat: bool,
+    /// Custom shader, the primitive shader when `None`
+    pub shader_id: Option<usize>,
+    /// Camera slot uploaded with [`Renderer::upload_cameras`]
+    pub camera: usize,
+    /// Sub-rectangle of the target to draw into & clip to, keeps the pass's current one when `None`
+    pub viewport: Option<Viewport>,
+    /// Parameter block from [`Renderer::push_params`] for shaders loaded with
+    /// [`Renderer::add_shader_with_params`], a zeroed block when `None`
+    pub params: Option<u32>,
+    /// Textures for the extra groups of shaders loaded with [`Renderer::add_shader_with_bindings`],
+    /// the default white texture when `None`
+    pub extra_textures: [Option<usize>; MAX_SHADER_TEXTURES],
+}
+
+/// Pixel rectangle of a render target, top-left origin
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Viewport covering a whole `width` x `height` target
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+    /// Whether the viewport covers no pixels
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Uniform buffer holding view-projection matrices, one per slot at `stride` bytes
+struct CameraRing {
+    buffer: Buffer,
+    bind_group: BindGroup,
+    capacity: usize,
+    stride: u64,
+}
+
+impl CameraRing {
+    fn new(device: &Device, layout: &BindGroupLayout, capacity: usize) -> Self {
+        let align = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = (size_of::<CameraUniform>() as u64).next_multiple_of(align);
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Camera Buffer"),
+            size: stride * capacity as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: BufferSize::new(size_of::<CameraUniform>() as u64),
+                }),
+            }],
+        });
+        Self {
+            buffer,
+            bind_group,
+            capacity,
+            stride,
+        }
+    }
+
+    /// Dynamic offset of a slot, falling back to slot 0 if it was never uploaded
+    fn offset(&self, slot: usize) -> u32 {
+        let slot = if slot < self.capacity { slot } else { 0 };
+        (slot as u64 * self.stride) as u32
+    }
+}
+
+/// Uniform buffer of per-draw parameter blocks at `stride` bytes, the first one stays zeroed
+struct ParamRing {
+    buffer: Buffer,
+    bind_group: BindGroup,
+    capacity: usize,
+    stride: u64,
+    /// Blocks pushed since the last [`Renderer::clear_params`], uploaded as a whole
+    staging: Vec<u8>,
+}
+
+impl ParamRing {
+    fn new(device: &Device, layout: &BindGroupLayout, capacity: usize) -> Self {
+        let align = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = PARAMS_BLOCK_SIZE.next_multiple_of(align);
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Params Buffer"),
+            size: stride * capacity as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &buffer,
+                    offset: 0,
+                    size: BufferSize::new(PARAMS_BLOCK_SIZE),
+                }),
+            }],
+        });
+        Self {
+            buffer,
+            bind_group,
+            capacity,
+            stride,
+            staging: vec![0; stride as usize],
+        }
+    }
+
+    /// Dynamic offset of a block, falling back to the zeroed one if it was never uploaded
+    fn offset(&self, params: Option<u32>) -> u32 {
+        params
+            .filter(|&offset| (offset as u64) < self.capacity as u64 * self.stride)
+            .unwrap_or(0)
+    }
+}
+
 /// Low-level GPU renderer built on `wgpu`
 ///
 /// Handles rendering pipelines, surface configuration, resources (textures, buffers), & drawing
 pub struct Renderer {
     pub(crate) gpu: Gpu,
     pipelines: Pipelines,
-    camera_bind_group: BindGroup,
-    camera_buffer: Buffer,
+    cameras: CameraRing,
     textures: Vec<Texture>,
     default_texture: Texture,
     clear_color: Color,
     surface_format: TextureFormat,
+    params: ParamRing,
     uniform_buffers: Vec<UniformEntry>,
-    shader_bindings: Vec<Vec<usize>>,
+    shader_bindings: Vec<ShaderBindings>,
+    shader_errors: BTreeMap<usize, ShaderError>,
 }
 
 impl Renderer {
@@ -73,28 +236,14 @@ impl Renderer {
         let surface_format = surface_config.format;
         let pipelines = Pipelines::new(&device, surface_format);
 
-        let camera_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: bytemuck::bytes_of(&CameraUniform {
-                view_proj: [
-                    [1.0, 0.0, 0.0, 0.0],
-                    [0.0, 1.0, 0.0, 0.0],
-                    [0.0, 0.0, 1.0, 0.0],
-                    [0.0, 0.0, 0.0, 1.0],
-                ],
-            }),
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-        });
-
-        let camera_bind_group = device.create_bind_group(&BindGroupDescriptor {
-            label: None,
-            layout: &pipelines.camera_layout,
-            entries: &[BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-        });
+        let cameras = CameraRing::new(&device, &pipelines.camera_layout, 2);
+        queue.write_buffer(
+            &cameras.buffer,
+            0,
+            bytemuck::bytes_of(&CameraUniform::new(IDENTITY)),
+        );
 
+        let params = ParamRing::new(&device, &pipelines.params_layout, 16);
         let default_texture = Texture::create_default(&device, &queue, &pipelines.texture_layout);
 
         Renderer {
@@ -105,14 +254,15 @@ impl Renderer {
                 queue,
             },
             pipelines,
-            camera_bind_group,
-            camera_buffer,
+            cameras,
             textures: Vec::new(),
             default_texture,
             clear_color: Color::BLACK,
             surface_format,
+            params,
             uniform_buffers: Vec::new(),
             shader_bindings: Vec::new(),
+            shader_errors: BTreeMap::new(),
         }
     }
 
@@ -143,6 +293,12 @@ impl Renderer {
         };
     }
 
+    /// Returns the clear color set by [`Self::set_clear_color`]
+    pub fn clear_color(&self) -> [f64; 4] {
+        let Color { r, g, b, a } = self.clear_color;
+        [r, g, b, a]
+    }
+
     /// Begins a frame with the given render target
     pub fn begin_frame(&mut self, target: &mut dyn RenderTarget) -> Option<Frame> {
         let (view, presentable) = target.acquire(&self.gpu.device)?;
@@ -179,7 +335,7 @@ impl Renderer {
         })
     }
 
-    /// Draws a geometry batch within an existing render pass
+    /// Draws a geometry batch within an existing render pass using camera slot 0
     pub fn draw_batch(
         &self,
         r_pass: &mut RenderPass<'_>,
@@ -187,27 +343,66 @@ impl Renderer {
         texture_id: Option<usize>,
         shader_id: Option<usize>,
     ) {
-        if batch.is_empty() {
+        let params = DrawParams {
+            texture_id,
+            shader_id,
+            ..Default::default()
+        };
+        self.draw(r_pass, batch, &params);
+    }
+
+    /// Draws a geometry batch within an existing render pass
+    pub fn draw(
+        &self,
+        r_pass: &mut RenderPass<'_>,
+        batch: &mut GeometryBatch,
+        params: &DrawParams,
+    ) {
+        let DrawParams {
+            texture_id,
+            repeat,
+            shader_id,
+            camera,
+            viewport,
+            params,
+            extra_textures,
+        } = *params;
+        if batch.is_empty() || viewport.is_some_and(|v| v.is_empty()) {
             return;
         }
         batch.upload(&self.gpu.device, &self.gpu.queue);
 
-        let texture = texture_id
-            .and_then(|id| self.textures.get(id))
-            .unwrap_or(&self.default_texture);
-
-        texture.bind(r_pass, 0);
+        let texture = self.texture_or_default(texture_id);
+        if repeat {
+            texture.bind_repeating(r_pass, 0);
+        } else {
+            texture.bind(r_pass, 0);
+        }
 
         let pipeline = shader_id
             .and_then(|id| self.pipelines.get_custom_pipeline(id))
             .unwrap_or(&self.pipelines.primitive);
 
         r_pass.set_pipeline(pipeline);
-        r_pass.set_bind_group(1, &self.camera_bind_group, &[]);
-
-        if let Some(uniform_ids) = shader_id.and_then(|id| self.shader_bindings.get(id)) {
-            for (i, &uid) in uniform_ids.iter().enumerate() {
-                r_pass.set_bind_group((2 + i) as u32, &self.uniform_buffers[uid].bind_group, &[]);
+        if let Some(viewport) = viewport {
+            self.set_viewport(r_pass, viewport);
+        }
+        r_pass.set_bind_group(1, &self.cameras.bind_group, &[self.cameras.offset(camera)]);
+
+        if let Some(bindings) = shader_id.and_then(|id| self.shader_bindings.get(id)) {
+            let mut group = shader::FIRST_UNIFORM_GROUP;
+            if bindings.params_size.is_some() {
+                let offset = self.params.offset(params);
+                r_pass.set_bind_group(group, &self.params.bind_group, &[offset]);
+                group += 1;
+            }
+            for &uid in &bindings.uniforms {
+                r_pass.set_bind_group(group, &self.uniform_buffers[uid].bind_group, &[]);
+                group += 1;
+            }
+            for &texture_id in &extra_textures[..bindings.textures] {
+                self.texture_or_default(texture_id).bind(r_pass, group);
+                group += 1;
             }
         }
 
@@ -215,13 +410,96 @@ impl Renderer {
         batch.clear();
     }
 
-    /// Uploads the given view-projection matrix to the GPU for use in vertex transforms
+    fn texture_or_default(&self, texture_id: Option<usize>) -> &Texture {
+        texture_id
+            .and_then(|id| self.textures.get(id))
+            .unwrap_or(&self.default_texture)
+    }
+
+    /// Maps clip space to `viewport` & clips later draws in the pass to it
+    ///
+    /// `viewport` must lie within the pass's target
+    pub fn set_viewport(&self, r_pass: &mut RenderPass<'_>, viewport: Viewport) {
+        let Viewport {
+            x,
+            y,
+            width,
+            height,
+        } = viewport;
+        r_pass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+        r_pass.set_scissor_rect(x, y, width, height);
+    }
+
+    /// Uploads the given view-projection matrix to camera slot 0 for use in vertex transforms
     pub fn upload_camera_matrix(&mut self, view_proj: [[f32; 4]; 4]) {
-        self.gpu.queue.write_buffer(
-            &self.camera_buffer,
-            0,
-            bytemuck::bytes_of(&CameraUniform { view_proj }),
+        self.upload_camera_matrices(&[view_proj]);
+    }
+
+    /// Uploads view-projection matrices into camera slots `0..n` with zeroed globals
+    pub fn upload_camera_matrices(&mut self, view_projs: &[[[f32; 4]; 4]]) {
+        let cameras: Vec<_> = view_projs.iter().map(|&m| CameraUniform::new(m)).collect();
+        self.upload_cameras(&cameras);
+    }
+
+    /// Uploads camera uniforms into slots `0..n`, selected per draw by [`DrawParams::camera`]
+    ///
+    /// Slots stay valid until the next upload, so upload once per frame before submitting draws
+    pub fn upload_cameras(&mut self, cameras: &[CameraUniform]) {
+        if cameras.is_empty() {
+            return;
+        }
+        if cameras.len() > self.cameras.capacity {
+            let capacity = cameras.len().next_power_of_two();
+            self.cameras =
+                CameraRing::new(&self.gpu.device, &self.pipelines.camera_layout, capacity);
+        }
+
+        let stride = self.cameras.stride as usize;
+        let mut data = vec![0u8; stride * cameras.len()];
+        for (slot, uniform) in data.chunks_exact_mut(stride).zip(cameras) {
+            slot[..size_of::<CameraUniform>()].copy_from_slice(bytemuck::bytes_of(uniform));
+        }
+        self.gpu.queue.write_buffer(&self.cameras.buffer, 0, &data);
+    }
+
+    /// Queues a per-draw parameter block & returns its offset for [`DrawParams::params`]
+    ///
+    /// Blocks accumulate until [`Self::clear_params`], call [`Self::upload_params`] before
+    /// submitting draws that use them  
+    /// Panics if `data` is larger than [`PARAMS_BLOCK_SIZE`]
+    pub fn push_params(&mut self, data: &[u8]) -> u32 {
+        assert!(
+            data.len() as u64 <= PARAMS_BLOCK_SIZE,
+            "parameter block of {} bytes exceeds {PARAMS_BLOCK_SIZE}",
+            data.len()
         );
+        let ring = &mut self.params;
+        let offset = ring.staging.len();
+        ring.staging.extend_from_slice(data);
+        ring.staging.resize(offset + ring.stride as usize, 0);
+        offset as u32
+    }
+
+    /// Uploads the blocks queued with [`Self::push_params`], growing the buffer if needed
+    pub fn upload_params(&mut self) {
+        let blocks = self.params.staging.len() / self.params.stride as usize;
+        if blocks > self.params.capacity {
+            let staging = std::mem::take(&mut self.params.staging);
+            self.params = ParamRing::new(
+                &self.gpu.device,
+                &self.pipelines.params_layout,
+                blocks.next_power_of_two(),
+            );
+            self.params.staging = staging;
+        }
+        self.gpu
+            .queue
+            .write_buffer(&self.params.buffer, 0, &self.params.staging);
+    }
+
+    /// Drops queued parameter blocks, call once per frame before pushing new ones
+    pub fn clear_params(&mut self) {
+        self.params.staging.truncate(self.params.stride as usize);
     }
 
     /// Create an offscreen render target
@@ -236,11 +514,7 @@ impl Renderer {
 
     /// Adds an offscreen target texture & returns its id
     pub fn add_offscreen_texture(&mut self, offscreen: &mut OffscreenTarget) -> usize {
-        let texture = Texture::from_view(
-            offscreen.view(),
-            &self.gpu.device,
-            &self.pipelines.texture_layout,
-        );
+        let texture = offscreen.as_texture(&self.gpu.device, &self.pipelines.texture_layout);
 
         if let Some(id) = offscreen.texture_id() {
             self.textures[id] = texture;
@@ -255,9 +529,14 @@ impl Renderer {
 
     /// Adds a new texture from image bytes & returns its id
     pub fn add_texture(&mut self, data: &[u8]) -> usize {
-        let img = image::load_from_memory(data).unwrap().to_rgba8();
+        self.try_add_texture(data).unwrap()
+    }
+
+    /// Adds a new texture from image bytes, or the error if they don't decode
+    pub fn try_add_texture(&mut self, data: &[u8]) -> Result<usize, ImageError> {
+        let img = image::load_from_memory(data)?.to_rgba8();
         let (w, h) = img.dimensions();
-        self.add_texture_raw(w, h, &img)
+        Ok(self.add_texture_raw(w, h, &img))
     }
 
     /// Adds a texture from raw RGBA bytes & returns its id
@@ -274,11 +553,22 @@ impl Renderer {
         texture_idx
     }
 
+    /// Returns the dimensions in pixels of a texture, if it exists
+    pub fn texture_size(&self, id: usize) -> Option<(u32, u32)> {
+        self.textures.get(id).and_then(Texture::size)
+    }
+
     /// Replaces an existing texture with new image data
     pub fn update_texture(&mut self, index: usize, data: &[u8]) {
-        let img = image::load_from_memory(data).unwrap().to_rgba8();
+        self.try_update_texture(index, data).unwrap()
+    }
+
+    /// Replaces an existing texture with new image data, keeping it if they don't decode
+    pub fn try_update_texture(&mut self, index: usize, data: &[u8]) -> Result<(), ImageError> {
+        let img = image::load_from_memory(data)?.to_rgba8();
         let (w, h) = img.dimensions();
-        self.update_texture_raw(index, w, h, &img)
+        self.update_texture_raw(index, w, h, &img);
+        Ok(())
     }
 
     /// Replaces an existing texture with raw RGBA bytes
@@ -294,20 +584,61 @@ impl Renderer {
     }
 
     /// Creates a custom shader pipeline from WGSL source code
-    /// Returns the pipeline index for use in draw calls
-    pub fn add_shader(&mut self, wgsl_source: &str) -> usize {
-        let id = self.pipelines.add_custom_pipeline(
-            &self.gpu.device,
-            self.surface_format,
-            wgsl_source,
-            &[],
-        );
-        self.shader_bindings.push(vec![]);
-        id
+    /// Returns the pipeline index for use in draw calls, or an error with the line & column
+    /// if the WGSL doesn't validate
+    pub fn add_shader(&mut self, wgsl_source: &str) -> Result<usize, ShaderError> {
+        self.add_shader_with_bindings(wgsl_source, &ShaderBindings::default())
+    }
+
+    /// Rebuilds a custom shader from new WGSL source, keeping its bindings  
+    /// On error the previous pipeline stays in use & the error is kept in
+    /// [`Self::shader_errors`] until a reload succeeds
+    pub fn reload_shader(
+        &mut self,
+        shader_id: usize,
+        wgsl_source: &str,
+    ) -> Result<(), ShaderError> {
+        self.reload_shader_mapped(shader_id, wgsl_source, &SourceMap::default())
+    }
+
+    /// Like [`Self::reload_shader`] for [`crate::Preprocessor`] output, errors point at the
+    /// lines `source_map` traces them back to
+    pub fn reload_shader_mapped(
+        &mut self,
+        shader_id: usize,
+        wgsl_source: &str,
+        source_map: &SourceMap,
+    ) -> Result<(), ShaderError> {
+        let Some(bindings) = self.shader_bindings.get(shader_id).cloned() else {
+            return Err(ShaderError::UnknownShader(shader_id));
+        };
+        match self.build_shader(wgsl_source, &bindings) {
+            Ok(pipeline) => {
+                self.pipelines.custom[shader_id] = pipeline;
+                self.shader_errors.remove(&shader_id);
+                Ok(())
+            }
+            Err(err) => {
+                let err = source_map.remap(err);
+                self.shader_errors.insert(shader_id, err.clone());
+                Err(err)
+            }
+        }
+    }
+
+    /// Shaders whose last [`Self::reload_shader`] failed, with the error
+    pub fn shader_errors(&self) -> impl Iterator<Item = (usize, &ShaderError)> {
+        self.shader_errors.iter().map(|(&id, err)| (id, err))
+    }
+
+    /// Creates a uniform buffer holding `value`, bind it with [`Self::add_shader_with_uniforms`]
+    pub fn create_uniform<T: Pod>(&mut self, value: &T) -> Uniform<T> {
+        let id = self.add_uniform(bytemuck::bytes_of(value));
+        let buffer = self.uniform_buffers[id].buffer.clone();
+        Uniform::new(id, buffer, self.gpu.queue.clone())
     }
 
-    /// Creates a uniform buffer and returns its id
-    pub fn add_uniform(&mut self, data: &[u8]) -> usize {
+    fn add_uniform(&mut self, data: &[u8]) -> usize {
         let buffer = self.gpu.device.create_buffer_init(&BufferInitDescriptor {
             label: Some("User Uniform Buffer"),
             contents: data,
@@ -349,27 +680,110 @@ impl Renderer {
         id
     }
 
-    /// Updates an existing uniform buffer with new data
-    pub fn update_uniform(&mut self, id: usize, data: &[u8]) {
-        self.gpu
-            .queue
-            .write_buffer(&self.uniform_buffers[id].buffer, 0, data);
+    /// Creates a custom shader pipeline with uniform buffers bound from `@group(2)` on  
+    /// Returns the pipeline index for use in draw calls, or an error if the WGSL doesn't
+    /// parse, an id isn't a uniform buffer or a buffer's size differs from the uniform
+    /// declared for its group
+    pub fn add_shader_with_uniforms(
+        &mut self,
+        wgsl_source: &str,
+        uniform_ids: &[usize],
+    ) -> Result<usize, ShaderError> {
+        let bindings = ShaderBindings::default().with_uniforms(uniform_ids);
+        self.add_shader_with_bindings(wgsl_source, &bindings)
+    }
+
+    /// Creates a custom shader pipeline reading a per-draw parameter block of `params_size`
+    /// bytes at `@group(2)`, with uniform buffers bound from `@group(3)` on  
+    /// Blocks are queued with [`Self::push_params`] & selected by [`DrawParams::params`]
+    pub fn add_shader_with_params(
+        &mut self,
+        wgsl_source: &str,
+        params_size: u64,
+        uniform_ids: &[usize],
+    ) -> Result<usize, ShaderError> {
+        let bindings = ShaderBindings {
+            params_size: Some(params_size),
+            ..ShaderBindings::default().with_uniforms(uniform_ids)
+        };
+        self.add_shader_with_bindings(wgsl_source, &bindings)
     }
 
-    /// Creates a custom shader pipeline with associated uniform buffers
-    /// Returns the pipeline index for use in draw calls
-    pub fn add_shader_with_uniforms(&mut self, wgsl_source: &str, uniform_ids: &[usize]) -> usize {
-        let layouts: Vec<&BindGroupLayout> = uniform_ids
-            .iter()
-            .map(|&id| &self.uniform_buffers[id].layout)
+    /// Creates a custom shader pipeline with a parameter block, uniform buffers & extra
+    /// textures bound from `@group(2)` on, see [`ShaderBindings`] for the group order  
+    /// Errors if the bindings don't fit the device or don't match what the WGSL declares
+    pub fn add_shader_with_bindings(
+        &mut self,
+        wgsl_source: &str,
+        bindings: &ShaderBindings,
+    ) -> Result<usize, ShaderError> {
+        let pipeline = self.build_shader(wgsl_source, bindings)?;
+        self.pipelines.custom.push(pipeline);
+        self.shader_bindings.push(bindings.clone());
+        Ok(self.pipelines.custom.len() - 1)
+    }
+
+    /// Validates `wgsl_source` against `bindings` & creates its pipeline
+    fn build_shader(
+        &self,
+        wgsl_source: &str,
+        bindings: &ShaderBindings,
+    ) -> Result<RenderPipeline, ShaderError> {
+        if let Some(size) = bindings.params_size.filter(|&s| s > PARAMS_BLOCK_SIZE) {
+            return Err(ShaderError::ParamsTooLarge {
+                size,
+                max: PARAMS_BLOCK_SIZE,
+            });
+        }
+        if bindings.textures > MAX_SHADER_TEXTURES {
+            return Err(ShaderError::TooManyTextures {
+                count: bindings.textures,
+                max: MAX_SHADER_TEXTURES,
+            });
+        }
+        let max_groups = self.gpu.device.limits().max_bind_groups;
+        if bindings.group_count() > max_groups {
+            return Err(ShaderError::TooManyGroups {
+                needed: bindings.group_count(),
+                max: max_groups,
+            });
+        }
+        let uniforms = bindings.uniform_entries(&self.uniform_buffers)?;
+        let sizes: Vec<u64> = bindings
+            .params_size
+            .into_iter()
+            .chain(uniforms.iter().map(|entry| entry.buffer.size()))
             .collect();
-        let id = self.pipelines.add_custom_pipeline(
+        shader::check_bindings(wgsl_source, &sizes, bindings.textures)?;
+
+        let params_layout = bindings.params_size.map(|_| &self.pipelines.params_layout);
+        let texture_layout = &self.pipelines.texture_layout;
+        let layouts: Vec<&BindGroupLayout> = params_layout
+            .into_iter()
+            .chain(uniforms.iter().map(|entry| &entry.layout))
+            .chain(std::iter::repeat_n(texture_layout, bindings.textures))
+            .collect();
+
+        // naga can't see how the pipeline uses the shader, e.g. a `Globals` larger than the
+        // camera uniform or vertex inputs that don't match `Vertex`, so catch wgpu's errors
+        // instead of letting them panic
+        self.gpu.device.push_error_scope(ErrorFilter::Validation);
+        let pipeline = self.pipelines.create_custom_pipeline(
             &self.gpu.device,
             self.surface_format,
             wgsl_source,
             &layouts,
         );
-        self.shader_bindings.push(uniform_ids.to_vec());
-        id
+        let error = pin!(self.gpu.device.pop_error_scope());
+        // native wgpu resolves the scope right away, WebGPU only asynchronously so it's
+        // left to the console there
+        match error.poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(Some(error)) => Err(ShaderError::Validation(ShaderDiagnostic {
+                message: error.to_string(),
+                location: None,
+                snippet: String::new(),
+            })),
+            _ => Ok(pipeline),
+        }
     }
 }
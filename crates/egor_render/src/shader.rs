@@ -0,0 +1,462 @@
+use std::fmt;
+
+use bytemuck::Pod;
+use naga::{
+    AddressSpace, Module, ShaderStage, SourceLocation, TypeInner,
+    front::wgsl,
+    proc::Layouter,
+    valid::{Capabilities, ValidationFlags, Validator},
+};
+
+/// First bind group of user uniforms, after textures (0) & camera globals (1)
+pub(crate) const FIRST_UNIFORM_GROUP: u32 = 2;
+/// Entry points every custom shader must define
+pub(crate) const VERTEX_ENTRY: &str = "vs_main";
+pub(crate) const FRAGMENT_ENTRY: &str = "fs_main";
+
+/// Most extra textures a custom shader can sample, see [`ShaderBindings::textures`]
+pub const MAX_SHADER_TEXTURES: usize = 2;
+
+/// Resources a custom shader reads after the texture (0) & globals (1) groups
+///
+/// Each gets its own group, in order: the per-draw parameter block, the uniform buffers,
+/// then the extra textures as a `@binding(0)` texture & `@binding(1)` sampler
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShaderBindings {
+    /// Size in bytes of a per-draw parameter block, see [`crate::Renderer::push_params`]
+    pub params_size: Option<u64>,
+    /// Uniform buffers from [`crate::Renderer::create_uniform`]
+    pub uniforms: Vec<usize>,
+    /// Number of extra textures, set per draw with [`crate::DrawParams::extra_textures`]
+    pub textures: usize,
+}
+
+impl ShaderBindings {
+    /// Reads a per-draw `T` at the first group
+    pub fn with_params<T: Pod>(mut self) -> Self {
+        self.params_size = Some(size_of::<T>() as u64);
+        self
+    }
+    /// Binds uniform buffers after the parameter block
+    pub fn with_uniforms(mut self, uniform_ids: &[usize]) -> Self {
+        self.uniforms = uniform_ids.to_vec();
+        self
+    }
+    /// Binds `count` extra textures after the uniform buffers
+    pub fn with_textures(mut self, count: usize) -> Self {
+        self.textures = count;
+        self
+    }
+
+    /// Number of bind groups the shader's pipeline uses, including the texture & globals groups
+    pub fn group_count(&self) -> u32 {
+        FIRST_UNIFORM_GROUP + self.buffer_groups() + self.textures as u32
+    }
+    /// Groups holding the parameter block & uniform buffers
+    pub(crate) fn buffer_groups(&self) -> u32 {
+        self.params_size.is_some() as u32 + self.uniforms.len() as u32
+    }
+    /// Looks up each uniform id in `buffers`, in binding order
+    pub(crate) fn uniform_entries<'a, T>(
+        &self,
+        buffers: &'a [T],
+    ) -> Result<Vec<&'a T>, ShaderError> {
+        self.uniforms
+            .iter()
+            .map(|&id| buffers.get(id).ok_or(ShaderError::UnknownUniform(id)))
+            .collect()
+    }
+}
+
+/// What went wrong in WGSL source & where
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderDiagnostic {
+    pub message: String,
+    /// 1-based line & column (in bytes) of the error, when naga can point at one
+    pub location: Option<(u32, u32)>,
+    /// The offending source line with the error underlined, empty without a location
+    pub snippet: String,
+}
+
+impl ShaderDiagnostic {
+    fn new(message: String, source: &str, location: Option<SourceLocation>) -> Self {
+        let snippet = location.map_or_else(String::new, |loc| snippet(source, loc));
+        Self {
+            message,
+            location: location.map(|loc| (loc.line_number, loc.line_position)),
+            snippet,
+        }
+    }
+
+    /// Diagnostic underlining a whole 1-based `line` of `source`
+    pub(crate) fn at_line(message: String, source: &str, line: u32) -> Self {
+        let length = source.lines().nth(line as usize - 1).map_or(0, str::len);
+        let location = SourceLocation {
+            line_number: line,
+            line_position: 1,
+            offset: 0,
+            length: length as u32,
+        };
+        Self::new(message, source, Some(location))
+    }
+
+    /// Moves the diagnostic to `line`, naming `file` in the message if given
+    pub(crate) fn relocate(&mut self, line: u32, file: Option<&str>) {
+        let Some((old_line, column)) = self.location else {
+            return;
+        };
+        let (old_gutter, gutter) = (old_line.to_string().len(), line.to_string());
+        self.snippet = self
+            .snippet
+            .lines()
+            .enumerate()
+            .map(|(i, text)| {
+                let rest = text.get(old_gutter..).unwrap_or(text);
+                match i {
+                    0 => format!("{gutter}{rest}"),
+                    _ => format!("{}{rest}", " ".repeat(gutter.len())),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.location = Some((line, column));
+        if let Some(file) = file {
+            self.message = format!("{} in `{file}`", self.message);
+        }
+    }
+}
+
+impl fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some((line, column)) = self.location {
+            write!(f, " at {line}:{column}\n{}", self.snippet)?;
+        }
+        Ok(())
+    }
+}
+
+/// Source line at `loc` with a caret line under the span
+fn snippet(source: &str, loc: SourceLocation) -> String {
+    let line = source
+        .lines()
+        .nth(loc.line_number.saturating_sub(1) as usize)
+        .unwrap_or_default();
+    let start = (loc.line_position.saturating_sub(1) as usize).min(line.len());
+    let width = (loc.length as usize).clamp(1, (line.len() - start).max(1));
+    let gutter = loc.line_number.to_string();
+    format!(
+        "{gutter} | {line}\n{} | {}{}",
+        " ".repeat(gutter.len()),
+        " ".repeat(start),
+        "^".repeat(width)
+    )
+}
+
+/// Why a custom shader could not be created
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShaderError {
+    /// A directive failed to expand, see [`crate::Preprocessor`]
+    Preprocess(ShaderDiagnostic),
+    /// The WGSL source failed to parse
+    Parse(ShaderDiagnostic),
+    /// The WGSL parsed but is invalid, e.g. mismatched types or unsupported uniform types
+    Validation(ShaderDiagnostic),
+    /// The shader lacks `vs_main` or `fs_main`
+    MissingEntryPoint(&'static str),
+    /// A uniform buffer's size differs from the struct the shader declares for it
+    UniformSize {
+        group: u32,
+        name: String,
+        /// Size of the WGSL type in bytes
+        expected: u64,
+        /// Size of the bound buffer in bytes
+        actual: u64,
+    },
+    /// The shader reads a uniform group no buffer is bound to
+    MissingUniform { group: u32, name: String },
+    /// No uniform buffer was created with this id
+    UnknownUniform(usize),
+    /// A per-draw parameter block is larger than [`crate::PARAMS_BLOCK_SIZE`]
+    ParamsTooLarge { size: u64, max: u64 },
+    /// The shader samples a texture or sampler outside the extra texture groups
+    MissingTexture { group: u32, name: String },
+    /// More extra textures than [`MAX_SHADER_TEXTURES`]
+    TooManyTextures { count: usize, max: usize },
+    /// The bindings need more groups than the device supports
+    TooManyGroups { needed: u32, max: u32 },
+    /// No shader was loaded with this id
+    UnknownShader(usize),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preprocess(diagnostic) => write!(f, "preprocessor error: {diagnostic}"),
+            Self::Parse(diagnostic) => write!(f, "WGSL parse error: {diagnostic}"),
+            Self::Validation(diagnostic) => write!(f, "invalid WGSL: {diagnostic}"),
+            Self::MissingEntryPoint(name) => write!(f, "shader has no `{name}` entry point"),
+            Self::UniformSize {
+                group,
+                name,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "uniform `{name}` at @group({group}) is {expected} bytes in WGSL but the \
+                     bound buffer is {actual} bytes"
+                )?;
+                if actual < expected {
+                    write!(
+                        f,
+                        ", check for padding: vec3 & structs align to 16 bytes in uniforms"
+                    )?;
+                }
+                Ok(())
+            }
+            Self::MissingUniform { group, name } => write!(
+                f,
+                "uniform `{name}` at @group({group}) has no buffer bound, pass one per group \
+                 starting at @group({FIRST_UNIFORM_GROUP})"
+            ),
+            Self::UnknownUniform(id) => write!(f, "no uniform buffer was created with id {id}"),
+            Self::ParamsTooLarge { size, max } => write!(
+                f,
+                "parameter block is {size} bytes, per-draw params are limited to {max}, \
+                 use a uniform buffer instead"
+            ),
+            Self::MissingTexture { group, name } => write!(
+                f,
+                "`{name}` at @group({group}) is a texture or sampler but no extra texture is \
+                 bound to that group, declare textures after the uniform groups"
+            ),
+            Self::TooManyTextures { count, max } => {
+                write!(
+                    f,
+                    "{count} extra textures requested, at most {max} are supported"
+                )
+            }
+            Self::TooManyGroups { needed, max } => write!(
+                f,
+                "shader needs {needed} bind groups but the device supports {max}, \
+                 combine uniforms into one struct"
+            ),
+            Self::UnknownShader(id) => write!(f, "no shader was loaded with id {id}"),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// Parses & validates WGSL, so errors surface as [`ShaderError`]s instead of wgpu panics
+fn validate(wgsl_source: &str) -> Result<Module, ShaderError> {
+    let module = wgsl::parse_str(wgsl_source).map_err(|e| {
+        let location = e.location(wgsl_source);
+        ShaderError::Parse(ShaderDiagnostic::new(
+            e.message().to_string(),
+            wgsl_source,
+            location,
+        ))
+    })?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|e| {
+            // naga nests the specific cause under the function or global it occurred in
+            let mut message = e.as_inner().to_string();
+            let mut cause = std::error::Error::source(e.as_inner());
+            while let Some(inner) = cause {
+                message = format!("{message}: {inner}");
+                cause = inner.source();
+            }
+            let location = e.location(wgsl_source);
+            ShaderError::Validation(ShaderDiagnostic::new(message, wgsl_source, location))
+        })?;
+
+    for (stage, name) in [
+        (ShaderStage::Vertex, VERTEX_ENTRY),
+        (ShaderStage::Fragment, FRAGMENT_ENTRY),
+    ] {
+        if !module
+            .entry_points
+            .iter()
+            .any(|ep| ep.stage == stage && ep.name == name)
+        {
+            return Err(ShaderError::MissingEntryPoint(name));
+        }
+    }
+    Ok(module)
+}
+
+/// Validates a custom shader, then checks the uniforms it declares from
+/// [`FIRST_UNIFORM_GROUP`] on against the sizes of the buffers bound to them in group
+/// order, & that textures & samplers sit in the `textures` groups after them
+pub(crate) fn check_bindings(
+    wgsl_source: &str,
+    buffer_sizes: &[u64],
+    textures: usize,
+) -> Result<(), ShaderError> {
+    let module = validate(wgsl_source)?;
+    let mut layouter = Layouter::default();
+    // Layout errors are reported by wgpu's own validation when the pipeline is created
+    if layouter.update(module.to_ctx()).is_err() {
+        return Ok(());
+    }
+
+    for (_, var) in module.global_variables.iter() {
+        let Some(binding) = &var.binding else {
+            continue;
+        };
+        if binding.group < FIRST_UNIFORM_GROUP {
+            continue;
+        }
+        let name = var.name.clone().unwrap_or_default();
+        let group = binding.group;
+        if matches!(
+            module.types[var.ty].inner,
+            TypeInner::Image { .. } | TypeInner::Sampler { .. }
+        ) {
+            let first = FIRST_UNIFORM_GROUP + buffer_sizes.len() as u32;
+            if !(first..first + textures as u32).contains(&group) {
+                return Err(ShaderError::MissingTexture { group, name });
+            }
+            continue;
+        }
+        if var.space != AddressSpace::Uniform {
+            continue;
+        }
+        let Some(&actual) = buffer_sizes.get((group - FIRST_UNIFORM_GROUP) as usize) else {
+            return Err(ShaderError::MissingUniform { group, name });
+        };
+        let expected = layouter[var.ty].size as u64;
+        if expected != actual {
+            return Err(ShaderError::UniformSize {
+                group,
+                name,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY_POINTS: &str = "
+        @vertex fn vs_main() -> @builtin(position) vec4<f32> { return vec4<f32>(0.0); }
+        @fragment fn fs_main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }
+    ";
+    const UNIFORMS: &str = "
+        struct Params { color: vec3<f32>, strength: f32, }
+        @group(2) @binding(0) var<uniform> params: Params;
+        @group(3) @binding(0) var<uniform> time: f32;
+    ";
+
+    fn shader(declarations: &str) -> String {
+        format!("{declarations}{ENTRY_POINTS}")
+    }
+
+    #[test]
+    fn matching_sizes_pass() {
+        assert_eq!(check_bindings(&shader(UNIFORMS), &[16, 4], 0), Ok(()));
+    }
+
+    #[test]
+    fn size_mismatch_names_uniform() {
+        // e.g. a Rust struct that left out `strength`
+        let err = check_bindings(&shader(UNIFORMS), &[12, 4], 0).unwrap_err();
+        assert_eq!(
+            err,
+            ShaderError::UniformSize {
+                group: 2,
+                name: "params".into(),
+                expected: 16,
+                actual: 12,
+            }
+        );
+        assert!(err.to_string().contains("padding"));
+    }
+
+    #[test]
+    fn missing_buffer() {
+        let err = check_bindings(&shader(UNIFORMS), &[16], 0).unwrap_err();
+        assert!(matches!(err, ShaderError::MissingUniform { group: 3, .. }));
+    }
+
+    #[test]
+    fn parse_errors_point_at_source() {
+        let source = shader("fn broken() {\n    let x = 1.0 +;\n}\n");
+        let Err(ShaderError::Parse(diagnostic)) = check_bindings(&source, &[], 0) else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(diagnostic.location.map(|(line, _)| line), Some(2));
+        assert!(diagnostic.snippet.starts_with("2 |     let x = 1.0 +;"));
+        assert!(diagnostic.snippet.contains('^'));
+    }
+
+    #[test]
+    fn validation_errors() {
+        // parses fine, but bool isn't allowed in a uniform
+        let source = shader("@group(2) @binding(0) var<uniform> flag: bool;");
+        let err = check_bindings(&source, &[4], 0).unwrap_err();
+        assert!(matches!(err, ShaderError::Validation(_)), "{err}");
+    }
+
+    #[test]
+    fn entry_points_are_required() {
+        let source =
+            "@vertex fn vs_main() -> @builtin(position) vec4<f32> { return vec4<f32>(0.0); }";
+        assert_eq!(
+            check_bindings(source, &[], 0),
+            Err(ShaderError::MissingEntryPoint(FRAGMENT_ENTRY))
+        );
+    }
+
+    #[test]
+    fn textures_follow_uniforms() {
+        let source = shader(
+            "
+            @group(2) @binding(0) var<uniform> time: f32;
+            @group(3) @binding(0) var noise: texture_2d<f32>;
+            @group(3) @binding(1) var noise_sampler: sampler;
+        ",
+        );
+        assert_eq!(check_bindings(&source, &[4], 1), Ok(()));
+        // declared without the texture, or with the texture where a uniform goes
+        assert!(matches!(
+            check_bindings(&source, &[4], 0),
+            Err(ShaderError::MissingTexture { group: 3, .. })
+        ));
+        assert!(matches!(
+            check_bindings(&source, &[4, 4], 1),
+            Err(ShaderError::MissingTexture { group: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn group_count() {
+        let bindings = ShaderBindings::default()
+            .with_params::<[f32; 4]>()
+            .with_textures(1);
+        assert_eq!(bindings.params_size, Some(16));
+        assert_eq!(bindings.group_count(), 4);
+    }
+
+    #[test]
+    fn unknown_uniform_ids() {
+        let buffers = ["params", "time"];
+        let bindings = ShaderBindings::default().with_uniforms(&[1, 0]);
+        assert_eq!(
+            bindings.uniform_entries(&buffers),
+            Ok(vec![&"time", &"params"])
+        );
+        assert_eq!(
+            bindings.with_uniforms(&[0, 2]).uniform_entries(&buffers),
+            Err(ShaderError::UnknownUniform(2))
+        );
+    }
+}
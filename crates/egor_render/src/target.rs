@@ -1,5 +1,5 @@
 use wgpu::{
-    Adapter, BindGroupLayout, Device, Extent3d, Instance, PresentMode, Surface,
+    Adapter, BindGroupLayout, Device, Extent3d, FilterMode, Instance, PresentMode, Surface,
     SurfaceConfiguration, SurfaceTarget, TextureDescriptor, TextureDimension, TextureFormat,
     TextureUsages, TextureView, WindowHandle,
 };
@@ -91,6 +91,7 @@ pub struct OffscreenTarget {
     width: u32,
     height: u32,
     texture_id: Option<usize>,
+    filter: FilterMode,
 }
 
 impl OffscreenTarget {
@@ -137,11 +138,23 @@ impl OffscreenTarget {
             width,
             height,
             texture_id: None,
+            filter: FilterMode::Linear,
         }
     }
 
     pub fn as_texture(&self, device: &Device, layout: &BindGroupLayout) -> Texture {
-        Texture::from_view(&self.sample_view, device, layout)
+        Texture::from_view_filtered(&self.sample_view, device, layout, self.filter)
+            .with_size(self.width, self.height)
+    }
+
+    /// Filter used when sampling the target as a texture, linear by default  
+    /// Takes effect the next time the texture is registered with the renderer
+    pub fn set_filter(&mut self, filter: FilterMode) {
+        self.filter = filter;
+    }
+
+    pub fn filter(&self) -> FilterMode {
+        self.filter
     }
 
     pub fn texture(&self) -> &wgpu::Texture {
@@ -196,7 +209,10 @@ impl RenderTarget for OffscreenTarget {
         if self.width == w && self.height == h {
             return;
         }
-        // recreate the texture with new dimensions
+        // recreate the texture with new dimensions, keeping its renderer slot
+        let (texture_id, filter) = (self.texture_id, self.filter);
         *self = Self::new(device, w, h, self.format);
+        self.texture_id = texture_id;
+        self.filter = filter;
     }
 }
@@ -8,8 +8,11 @@ use wgpu::{
 /// A GPU texture that can be bound in shaders for rendering
 ///
 /// Wraps a `wgpu::Texture`, its view, sampler, & bind group  
+/// A second bind group samples with wrapping UVs for tiled fills
 pub struct Texture {
     bind_group: BindGroup,
+    repeat_bind_group: BindGroup,
+    size: Option<(u32, u32)>,
 }
 
 impl Texture {
@@ -62,23 +65,8 @@ impl Texture {
         );
 
         let view = texture.create_view(&Default::default());
-        let sampler = device.create_sampler(&Default::default());
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
-            label: None,
-            layout: bind_group_layout,
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(&view),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
-
-        Self { bind_group }
+        Self::from_view_filtered(&view, device, bind_group_layout, FilterMode::Nearest)
+            .with_size(width, height)
     }
 
     /// Creates a bindable texture from an existing GPU texture view.
@@ -87,30 +75,52 @@ impl Texture {
     /// It wraps a view produced elsewhere (an offscreen render target)
     /// and builds the bind group required for sampling in shaders
     pub fn from_view(view: &TextureView, device: &Device, layout: &BindGroupLayout) -> Self {
-        let sampler = device.create_sampler(&SamplerDescriptor {
-            address_mode_u: AddressMode::ClampToEdge,
-            address_mode_v: AddressMode::ClampToEdge,
-            mag_filter: FilterMode::Linear,
-            min_filter: FilterMode::Linear,
-            ..Default::default()
-        });
+        Self::from_view_filtered(view, device, layout, FilterMode::Linear)
+    }
 
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
-            label: None,
-            layout,
-            entries: &[
-                BindGroupEntry {
-                    binding: 0,
-                    resource: BindingResource::TextureView(view),
-                },
-                BindGroupEntry {
-                    binding: 1,
-                    resource: BindingResource::Sampler(&sampler),
-                },
-            ],
-        });
+    /// Like [`Self::from_view`] but magnifies & minifies with `filter`,
+    /// e.g. [`FilterMode::Nearest`] for pixel art
+    pub fn from_view_filtered(
+        view: &TextureView,
+        device: &Device,
+        layout: &BindGroupLayout,
+        filter: FilterMode,
+    ) -> Self {
+        let bind_group = |address_mode| {
+            let sampler = device.create_sampler(&SamplerDescriptor {
+                address_mode_u: address_mode,
+                address_mode_v: address_mode,
+                mag_filter: filter,
+                min_filter: filter,
+                ..Default::default()
+            });
+            device.create_bind_group(&BindGroupDescriptor {
+                label: None,
+                layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                ],
+            })
+        };
+
+        Self {
+            bind_group: bind_group(AddressMode::ClampToEdge),
+            repeat_bind_group: bind_group(AddressMode::Repeat),
+            size: None,
+        }
+    }
 
-        Self { bind_group }
+    /// Records the dimensions of a texture made from a view, which can't report them
+    pub(crate) fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
     }
 
     /// Creates a 1×1 white fallback texture
@@ -120,10 +130,20 @@ impl Texture {
         Self::from_bytes(device, queue, layout, &[255u8, 255, 255, 255], 1, 1)
     }
 
+    /// Dimensions of the texture in pixels, `None` if made with [`Self::from_view`]
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.size
+    }
+
     /// Binds this texture at the given index in the render pass
     ///
     /// - `index` must match the bind group index used in the pipeline layout
     pub fn bind(&self, pass: &mut RenderPass, index: u32) {
         pass.set_bind_group(index, &self.bind_group, &[]);
     }
+
+    /// Like [`Self::bind`] but samples with wrapping UVs, for coordinates outside 0..1
+    pub fn bind_repeating(&self, pass: &mut RenderPass, index: u32) {
+        pass.set_bind_group(index, &self.repeat_bind_group, &[]);
+    }
 }
@@ -0,0 +1,43 @@
+use std::marker::PhantomData;
+
+use bytemuck::Pod;
+use wgpu::{Buffer, Queue};
+
+/// Typed handle to a uniform buffer created with [`crate::Renderer::create_uniform`]
+///
+/// `T` must match the WGSL struct it is bound to byte for byte, including padding
+/// Sizes are checked when the buffer is bound with [`crate::Renderer::add_shader_with_uniforms`]
+pub struct Uniform<T: Pod> {
+    id: usize,
+    buffer: Buffer,
+    queue: Queue,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Uniform<T> {
+    pub(crate) fn new(id: usize, buffer: Buffer, queue: Queue) -> Self {
+        Self {
+            id,
+            buffer,
+            queue,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Id to bind the buffer to a shader with
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Writes `value` to the buffer, visible from the next submitted frame
+    pub fn set(&self, value: &T) {
+        self.queue
+            .write_buffer(&self.buffer, 0, bytemuck::bytes_of(value));
+    }
+}
+
+impl<T: Pod> Clone for Uniform<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id, self.buffer.clone(), self.queue.clone())
+    }
+}
@@ -1,20 +1,21 @@
-struct Camera {
+struct Globals {
     view_proj: mat4x4<f32>,
+    time: f32,
 }
 
-@group(1) @binding(0) var<uniform> camera: Camera;
+@group(1) @binding(0) var<uniform> globals: Globals;
 @group(0) @binding(0) var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1) var s_diffuse: sampler;
 
 struct HealthBar {
     fill: f32,
-    time: f32,
     low_r: f32,
     low_g: f32,
     low_b: f32,
     high_r: f32,
     high_g: f32,
     high_b: f32,
+    _pad: f32,
 }
 
 @group(2) @binding(0) var<uniform> params: HealthBar;
@@ -34,7 +35,7 @@ struct VertexOutput {
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
-    output.clip_position = camera.view_proj * vec4<f32>(input.position, 0.0, 1.0);
+    output.clip_position = globals.view_proj * vec4<f32>(input.position, 0.0, 1.0);
     output.color = input.color;
     output.tex_coords = input.tex_coords;
     return output;
@@ -69,7 +70,7 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     let edge_dist = abs(fill_x - params.fill);
 
     let pulse_strength = mix(1.0, 0.0, params.fill);
-    let time_pulse = (sin(params.time * 10.0) + 1.0) / 2.0;
+    let time_pulse = (sin(globals.time * 10.0) + 1.0) / 2.0;
     let pulse = 1.0 - (time_pulse * pulse_strength * 0.5);
 
     let bloom = 3.0;
@@ -8,15 +8,14 @@ use egor::{
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 struct HealthBarParams {
     fill: f32,
-    time: f32,
     low_color: [f32; 3],
     high_color: [f32; 3],
+    // uniform structs are padded to 16 bytes
+    _pad: f32,
 }
 
 fn main() {
     let mut shader_id = 0;
-    let mut uniform_id = 0;
-    let mut elapsed = 0.;
 
     App::new()
         .title("Egor Health Bar Demo")
@@ -25,41 +24,42 @@ fn main() {
             gfx.clear(Color::new([0.1, 0.1, 0.15, 1.0]));
             let size = gfx.screen_size();
 
-            elapsed += timer.delta;
-
-            let health = ((0.5 * elapsed).sin() + 1.) / 2.;
-
             if timer.frame == 0 {
                 let wgsl = include_str!("../shaders/health_bar.wgsl");
-                let params = HealthBarParams {
-                    fill: 1.,
-                    time: 0.,
-                    low_color: [1., 0., 0.],
-                    high_color: [0., 1., 0.],
-                };
-                uniform_id = gfx.create_uniform(bytemuck::bytes_of(&params));
-                shader_id = gfx.load_shader_with_uniforms(wgsl, &[uniform_id]);
+                shader_id = gfx
+                    .load_shader_with_params::<HealthBarParams>(wgsl, &[])
+                    .expect("health bar shader");
             }
 
-            let params = HealthBarParams {
-                fill: health,
-                time: elapsed,
-                low_color: [1.0, 0.0, 0.0],
-                high_color: [0.0, 1.0, 0.0],
-            };
-            gfx.update_uniform(uniform_id, bytemuck::bytes_of(&params));
-
+            // two bars share the shader, each draw gets its own params
+            let bars = [
+                ("Player", 0.5, [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+                ("Boss", 0.2, [0.5, 0.0, 1.0], [1.0, 0.6, 0.0]),
+            ];
             let bar_size = vec2(300.0, 30.0);
-            let bar_pos = vec2((size.x - bar_size.x) * 0.5, size.y * 0.5 - bar_size.y * 0.5);
 
-            gfx.with_shader(shader_id, |gfx| {
-                gfx.rect().at(bar_pos).size(bar_size);
-            });
+            for (i, (name, speed, low_color, high_color)) in bars.into_iter().enumerate() {
+                let health = ((speed * timer.elapsed()).sin() + 1.) / 2.;
+                let params = HealthBarParams {
+                    fill: health,
+                    low_color,
+                    high_color,
+                    _pad: 0.0,
+                };
 
-            gfx.text(&format!("HP: {:.0}%", health * 100.0))
-                .at((size.x * 0.5 - 30.0, bar_pos.y - 30.0))
-                .size(20.0)
-                .color(Color::WHITE);
+                let bar_pos = vec2(
+                    (size.x - bar_size.x) * 0.5,
+                    size.y * 0.5 - bar_size.y * 0.5 + (i as f32 - 0.5) * 100.0,
+                );
+                gfx.with_shader_params(shader_id, &params, |gfx| {
+                    gfx.rect().at(bar_pos).size(bar_size);
+                });
+
+                gfx.text(&format!("{name}: {:.0}%", health * 100.0))
+                    .at((bar_pos.x, bar_pos.y - 30.0))
+                    .size(20.0)
+                    .color(Color::WHITE);
+            }
 
             gfx.text(&format!("FPS: {}", timer.fps))
                 .at(vec2(10.0, 10.0))
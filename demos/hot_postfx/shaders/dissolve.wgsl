@@ -0,0 +1,13 @@
+#include "noise.wgsl"
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let tex_color = textureSample(t_diffuse, s_diffuse, input.tex_coords);
+    let noise = textureSample(t_noise, s_noise, input.tex_coords).r;
+    let threshold = sin(globals.time * 0.5) * 0.5 + 0.5;
+    if noise < threshold {
+        discard;
+    }
+    let burn = 1.0 - smoothstep(threshold, threshold + 0.08, noise);
+    return vec4<f32>(mix(tex_color.rgb, vec3<f32>(1.0, 0.45, 0.1), burn), 1.0);
+}
@@ -0,0 +1,3 @@
+// Noise texture bound as the first extra texture, see `ShaderBindings::with_textures`
+@group(2) @binding(0) var t_noise: texture_2d<f32>;
+@group(2) @binding(1) var s_noise: sampler;
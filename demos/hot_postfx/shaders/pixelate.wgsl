@@ -1,7 +1,9 @@
+#define PIXEL_SIZE 8.0
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    let pixel_size = 8.0;
-    let resolution = vec2<f32>(800.0, 600.0);
+    let pixel_size = PIXEL_SIZE;
+    let resolution = globals.resolution;
     
     let pixelated_uv = floor(input.tex_coords * resolution / pixel_size) 
                        * pixel_size / resolution;
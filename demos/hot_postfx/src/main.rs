@@ -1,26 +1,48 @@
 use egor::{
     app::{App, FrameContext},
     math::{Vec2, vec2},
-    render::{Color, Graphics, OffscreenTarget, RenderTarget},
+    render::{Color, Graphics, OffscreenTarget, RenderTarget, ShaderBindings},
 };
 
-use std::fs;
+use std::{collections::HashMap, fs, time::SystemTime};
 
-fn load_effect(gfx: &mut Graphics, effect: &str) -> usize {
-    let common = fs::read_to_string("shaders/common.wgsl").unwrap();
-    let fragment = fs::read_to_string(format!("shaders/{effect}.wgsl")).unwrap();
-    gfx.load_shader(&(common + &fragment))
+const NOISE_SIZE: u32 = 64;
+
+/// Latest modification time of the shader files, to retry an effect that failed to load
+fn shaders_modified() -> Option<SystemTime> {
+    fs::read_dir("shaders")
+        .ok()?
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Grayscale value noise as RGBA8 pixels
+fn noise_pixels() -> Vec<u8> {
+    (0..NOISE_SIZE * NOISE_SIZE)
+        .flat_map(|i| {
+            let hash = i.wrapping_mul(2_654_435_761).rotate_left(13) ^ 0x5bd1_e995;
+            let v = (hash.wrapping_mul(0x27d4_eb2d) >> 24) as u8;
+            [v, v, v, 255]
+        })
+        .collect()
 }
 
 fn main() {
     let mut offscreen_target = None;
     let mut texture_id = 0;
+    let mut noise_id = 0;
+    let mut effects = HashMap::new();
+    // effect that failed to load & the shader files' state at the time
+    let mut failed = None;
 
     App::new()
         .title("Egor Hot Reload/Post Processing Demo")
         .window_size(800, 600)
-        .run(move |FrameContext { gfx, .. }| {
+        .run(move |FrameContext { gfx, timer, .. }| {
             gfx.clear(Color::WHITE);
+            if timer.frame == 0 {
+                noise_id = gfx.load_texture_raw(NOISE_SIZE, NOISE_SIZE, &noise_pixels());
+            }
             let size = gfx.screen_size();
             let center = size * 0.5;
 
@@ -36,8 +58,30 @@ fn main() {
             }
 
             // HOT RELOAD: change this line and save to swap effects live!
-            // Try swapping to: vignette, crt, pixelate
-            let shader = load_effect(gfx, "pixelate");
+            // Try swapping to: vignette, crt, pixelate, dissolve
+            let name = "pixelate";
+            // loaded once per effect, then rebuilt in place whenever its file changes
+            // a broken edit keeps the last good version, debug builds show the error on screen
+            let effect = match effects.get(name) {
+                Some(&id) => Some(id),
+                // a failed first load has nothing to rebuild, retry once a file changes
+                None if failed == Some((name, shaders_modified())) => None,
+                None => {
+                    // every effect gets the noise texture at @group(2), only dissolve samples it
+                    let bindings = ShaderBindings::default().with_textures(1);
+                    match gfx.load_shader_file(format!("shaders/{name}.wgsl"), &bindings) {
+                        Ok(id) => {
+                            effects.insert(name, id);
+                            Some(id)
+                        }
+                        Err(err) => {
+                            eprintln!("{err}");
+                            failed = Some((name, shaders_modified()));
+                            None
+                        }
+                    }
+                }
+            };
 
             gfx.render_offscreen(offscreen_target.as_mut().unwrap(), |gfx| {
                 gfx.rect()
@@ -55,12 +99,18 @@ fn main() {
                     .color(Color::new([1.0, 0.8, 0.2, 1.0]));
             });
 
-            gfx.with_shader(shader, |gfx| {
-                gfx.rect()
-                    .at(vec2(0., 0.))
-                    .size(size)
-                    .texture(texture_id)
-                    .color(Color::WHITE);
-            });
+            let draw_screen = |gfx: &mut Graphics| {
+                gfx.with_textures(&[noise_id], |gfx| {
+                    gfx.rect()
+                        .at(vec2(0., 0.))
+                        .size(size)
+                        .texture(texture_id)
+                        .color(Color::WHITE);
+                });
+            };
+            match effect {
+                Some(shader) => gfx.with_shader(shader, draw_screen),
+                None => draw_screen(gfx),
+            }
         });
 }
@@ -1,17 +1,15 @@
-mod animation;
-mod tilemap;
-
 use rand::Rng;
 
 use egor::{
+    animation::{Animation, Clip, PlayMode, SpriteSheet},
     app::{App, FrameContext, WindowEvent, egui::Window},
+    camera::{Camera, CameraFollow},
     input::{KeyCode, MouseButton},
     math::{Rect, Vec2, vec2},
-    render::{Align, Color, OffscreenTarget},
+    render::{Align, Color, Graphics},
+    tilemap::{TileLayer, TiledMap},
 };
 
-use crate::{animation::SpriteAnim, tilemap::EgorMap};
-
 const PLAYER_SIZE: f32 = 64.0;
 const BULLET_SIZE: Vec2 = vec2(5.0, 10.0);
 
@@ -34,14 +32,14 @@ struct Soldier {
 }
 
 struct GameState {
-    map: EgorMap,
-    minimap: Option<OffscreenTarget>,
-    minimap_tex: usize,
+    map: TiledMap,
+    layers: Vec<TileLayer>,
+    follow: CameraFollow,
     player: Soldier,
-    player_anim: SpriteAnim,
+    player_anim: Animation,
     player_tex: usize,
     enemies: Vec<Zombie>,
-    enemy_anim: SpriteAnim,
+    enemy_anim: Animation,
     enemy_tex: usize,
     bullets: Vec<Bullet>,
     wave: usize,
@@ -113,20 +111,33 @@ fn handle_bullet_hits(bullets: &mut Vec<Bullet>, enemies: &mut Vec<Zombie>, play
     kills
 }
 
+/// Loops the first `count` cells of a `columns` x `rows` sprite sheet, sized from the texture
+fn sheet_animation(
+    gfx: &Graphics,
+    texture: usize,
+    columns: usize,
+    rows: usize,
+    count: usize,
+) -> Animation {
+    let size = gfx.texture_size(texture).expect("sprite sheet loaded");
+    Animation::new(SpriteSheet::grid(size, columns, rows, count, 0.2).clip_all(PlayMode::Loop))
+}
+
 fn main() {
     let mut state = GameState {
-        map: EgorMap::new(include_str!("../assets/map.json")),
-        minimap: None,
-        minimap_tex: 0,
+        map: TiledMap::from_json(include_str!("../assets/map.json")).expect("parse map.json"),
+        layers: Vec::new(),
+        follow: CameraFollow::new().with_look_ahead(0.3),
         player: Soldier {
             rect: Rect::new(Vec2::ZERO, Vec2::splat(PLAYER_SIZE)),
             hp: 100.0,
             flash: 0.0,
         },
-        player_anim: SpriteAnim::new(3, 6, 16, 0.2),
+        // built from the sheet sizes once the textures are loaded
+        player_anim: Animation::new(Clip::default()),
         player_tex: 0,
         enemies: spawn_wave(Vec2::ZERO, 5, (50.0, 125.0), 1.0),
-        enemy_anim: SpriteAnim::new(2, 6, 11, 0.2),
+        enemy_anim: Animation::new(Clip::default()),
         enemy_tex: 0,
         bullets: vec![],
         wave: 1,
@@ -157,21 +168,28 @@ fn main() {
             }
 
             if timer.frame == 0 {
-                state.map.load_tileset(
-                    gfx,
-                    include_bytes!("../assets/otsp_tiles_01.png"),
-                    "otsp_tiles_01.png",
-                );
-                state.map.load_tileset(
-                    gfx,
-                    include_bytes!("../assets/otsp_walls_01.png"),
-                    "otsp_walls_01.png",
-                );
+                let tilesets: Vec<_> = state
+                    .map
+                    .tilesets
+                    .iter()
+                    .filter_map(|ts| {
+                        let bytes: &[u8] = match ts.image_name() {
+                            "otsp_tiles_01.png" => include_bytes!("../assets/otsp_tiles_01.png"),
+                            "otsp_walls_01.png" => include_bytes!("../assets/otsp_walls_01.png"),
+                            _ => return None,
+                        };
+                        Some(ts.tileset(gfx.load_texture(bytes)))
+                    })
+                    .collect();
+                state.layers = state.map.tile_layers(&tilesets);
                 state.player_tex = gfx.load_texture(include_bytes!("../assets/soldier.png"));
                 state.enemy_tex = gfx.load_texture(include_bytes!("../assets/zombie.png"));
-                let mut minimap = gfx.create_offscreen(200, 200);
-                state.minimap_tex = gfx.offscreen_as_texture(&mut minimap);
-                state.minimap = Some(minimap);
+                state.player_anim = sheet_animation(gfx, state.player_tex, 6, 3, 16);
+                state.enemy_anim = sheet_animation(gfx, state.enemy_tex, 6, 2, 11);
+                let screen_size = gfx.screen_size();
+                state
+                    .follow
+                    .snap(gfx.camera(), state.player.rect.position, screen_size);
                 return;
             }
 
@@ -185,38 +203,6 @@ fn main() {
                     .in_rect(Rect::new(Vec2::ZERO, screen_size), Align::MiddleCenter);
                 return;
             }
-            if let Some(minimap) = &mut state.minimap {
-                gfx.render_offscreen(minimap, |gfx| {
-                    gfx.clear(Color::BLACK);
-
-                    gfx.camera().set_zoom(0.15);
-                    gfx.camera()
-                        .center(state.player.rect.center(), vec2(200.0, 200.0));
-
-                    for e in &state.enemies {
-                        gfx.rect()
-                            .at(e.rect.position)
-                            .color(Color::RED)
-                            .size(Vec2::splat(48.0));
-                    }
-
-                    gfx.rect()
-                        .at(state.player.rect.position)
-                        .color(Color::GREEN)
-                        .texture(41);
-
-                    for b in &state.bullets {
-                        gfx.rect()
-                            .at(b.rect.position)
-                            .size(Vec2::splat(16.0))
-                            .color(Color::WHITE);
-                    }
-                });
-            }
-            let screen_half = screen_size / 2.0;
-            let position = state.player.rect.position - screen_half
-                + Into::<Vec2>::into(input.mouse_position());
-
             let dx = input.keys_held(&[KeyCode::KeyD, KeyCode::ArrowRight]) as i8
                 - input.keys_held(&[KeyCode::KeyA, KeyCode::ArrowLeft]) as i8;
             let dy = input.keys_held(&[KeyCode::KeyS, KeyCode::ArrowDown]) as i8
@@ -228,9 +214,19 @@ fn main() {
                 .rect
                 .translate(vec2(dx as f32, dy as f32) * 200.0 * timer.delta);
 
-            gfx.camera().center(state.player.rect.position, screen_size);
+            state.follow.update(
+                gfx.camera(),
+                state.player.rect.position,
+                screen_size,
+                timer.delta,
+            );
+            let position = gfx
+                .camera()
+                .screen_to_world(input.mouse_position().into(), screen_size);
             gfx.clear(Color::WHITE);
-            state.map.render(gfx);
+            for layer in &mut state.layers {
+                layer.draw(gfx);
+            }
 
             state.fire_cd -= timer.delta;
             if input.mouse_held(MouseButton::Left) && state.fire_cd <= 0.0 {
@@ -290,12 +286,11 @@ fn main() {
             let dir = position - state.player.rect.position;
             let angle = dir.y.atan2(dir.x);
 
-            let uv = if moving {
+            if moving {
                 state.player_anim.update(timer.delta);
-                state.player_anim.uv()
             } else {
-                state.player_anim.frame_uv(0)
-            };
+                state.player_anim.reset();
+            }
 
             gfx.rect()
                 .with(&state.player.rect)
@@ -306,7 +301,7 @@ fn main() {
                     Color::WHITE
                 })
                 .texture(state.player_tex)
-                .uv(uv);
+                .uv(state.player_anim.uv());
 
             if state.enemies.is_empty() {
                 state.wave += 1;
@@ -326,18 +321,39 @@ fn main() {
                 );
             }
 
-            if state.minimap.is_some() {
-                let screen_pos = vec2(screen_size.x - 210.0, 10.0);
-                let world_pos = gfx.camera().screen_to_world(screen_pos);
+            let mut minimap = Camera::default();
+            minimap.set_zoom(0.15);
+            minimap.center(state.player.rect.center(), vec2(200.0, 200.0));
+            let area = Rect::new(vec2(screen_size.x - 210.0, 10.0), vec2(200.0, 200.0));
+            gfx.viewport(area, &minimap, |gfx| {
+                gfx.screen_space(|gfx| {
+                    gfx.rect().size(vec2(200.0, 200.0)).color(Color::BLACK);
+                });
+
+                for e in &state.enemies {
+                    gfx.rect()
+                        .at(e.rect.position)
+                        .color(Color::RED)
+                        .size(Vec2::splat(48.0));
+                }
 
                 gfx.rect()
-                    .at(world_pos)
-                    .size(vec2(200.0, 200.0))
-                    .texture(state.minimap_tex);
-            }
+                    .at(state.player.rect.position)
+                    .color(Color::GREEN)
+                    .texture(41);
+
+                for b in &state.bullets {
+                    gfx.rect()
+                        .at(b.rect.position)
+                        .size(Vec2::splat(16.0))
+                        .color(Color::WHITE);
+                }
+            });
 
+            let culling = gfx.cull_stats();
             Window::new("Debug").show(egui_ctx, |ui| {
                 ui.label(format!("FPS: {}", timer.fps));
+                ui.label(format!("Culled: {}/{}", culling.culled, culling.submitted));
                 ui.label(format!("Wave: {}", state.wave));
                 ui.label(format!("Zombies killed: {}", state.kills));
                 ui.label(format!("HP: {:.0}", state.player.hp));
@@ -41,8 +41,11 @@
 //! Feature | Description | Default
 //! ---|---|---
 //! `log` | Enable logging via `egor_app/log` | opt-in
-//! `hot_reload` | Hot-reload support via `egor_glue/hot_reload` | opt-in
+//! `hot_reload` | Code hot-patching & file-watching shader/texture reloads via `egor_glue/hot_reload` | opt-in
 //! `ui`         | Enable egui integration via `egor_glue/ui` | opt-in
+//! `svg`        | SVG document loading via `egor_glue/svg` | opt-in
+//! `json`       | Aseprite, TexturePacker & Tiled JSON import via `egor_glue/json` | opt-in
+//! `tmx`        | Tiled TMX map import via `egor_glue/tmx` | opt-in
 //! `webgl`      | WebGL backend for `egor_render` | opt-in
 //! `angle`      | ANGLE backend for `egor_render` | opt-in
 //! `gles`       | OpenGL ES backend for `egor_render` | opt-in
@@ -57,6 +60,7 @@ pub mod app {
     #[cfg(target_os = "android")]
     pub use egor_app::{ANDROID_APP, AndroidApp};
     pub use egor_glue::app::{App, FrameContext};
+    pub use egor_glue::scaling::ScaleMode;
     #[cfg(feature = "ui")]
     pub use egor_glue::ui::egui;
 }
@@ -69,12 +73,38 @@ pub mod time {
     pub use egor_app::time::FrameTimer;
 }
 
+pub mod camera {
+    pub use egor_glue::camera::Camera;
+    pub use egor_glue::camera_controller::{CameraBindings, CameraController};
+    pub use egor_glue::camera_follow::CameraFollow;
+}
+
 pub mod render {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub use egor_glue::assets::AssetError;
+    #[cfg(feature = "svg")]
+    pub use egor_glue::svg::{SvgDocument, SvgError};
     pub use egor_glue::{
-        color::Color, graphics::Graphics, primitives::Anchor, primitives::PathStep,
-        primitives::Shape, text::Align,
+        color::Color, graphics::CullStats, graphics::Graphics, mesh::Mesh, primitives::Anchor,
+        primitives::FillRule, primitives::PathStep, primitives::Shape, primitives::UvMode,
+        text::Align,
     };
     pub use egor_render::target::{OffscreenTarget, RenderTarget};
+    pub use egor_render::{
+        Preprocessor, ShaderBindings, ShaderDiagnostic, ShaderError, SourceMap, Uniform,
+    };
+}
+
+pub mod animation {
+    #[cfg(feature = "json")]
+    pub use egor_glue::animation::AnimationError;
+    pub use egor_glue::animation::{Animation, Clip, Frame, PlayMode, SpriteSheet, Tag};
+}
+
+pub mod tilemap {
+    #[cfg(any(feature = "json", feature = "tmx"))]
+    pub use egor_glue::tiled::{TiledError, TiledLayer, TiledMap, TiledTileset};
+    pub use egor_glue::tilemap::{Tile, TileLayer, Tileset};
 }
 
 pub mod math {